@@ -0,0 +1,205 @@
+use plotly::common::{ColorScale, ColorScaleElement, Title};
+use plotly::layout::{Axis, Layout};
+use plotly::{HeatMap, Plot};
+use rayon::prelude::*;
+
+use crate::{midpoint_method, System2D};
+
+/// Long-time behaviour of a single initial condition.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fate {
+    /// Converged to the attractor with this index.
+    Attractor(usize),
+    /// Left the disk of radius `escape_radius`.
+    Unbounded,
+    /// Neither converged nor escaped within `max_time`.
+    Undecided,
+}
+
+pub struct BasinSettings {
+    pub dt: f64,
+    pub max_time: f64,
+    /// Distance to an attractor below which a trajectory counts as converged.
+    pub tolerance: f64,
+    pub escape_radius: f64,
+}
+
+impl Default for BasinSettings {
+    fn default() -> Self {
+        BasinSettings {
+            dt: 0.01,
+            max_time: 100.0,
+            tolerance: 1e-3,
+            escape_radius: 1e6,
+        }
+    }
+}
+
+/// Fates of a rectangular grid of initial conditions; `fates[j][i]` belongs
+/// to the point (`x_axis[i]`, `y_axis[j]`).
+pub struct BasinMap {
+    pub x_axis: Vec<f64>,
+    pub y_axis: Vec<f64>,
+    pub fates: Vec<Vec<Fate>>,
+    pub attractors_count: usize,
+}
+
+fn fate(
+    system: fn() -> System2D,
+    attractors: &[(f64, f64)],
+    x0: f64,
+    y0: f64,
+    settings: &BasinSettings,
+) -> Fate {
+    let mut t = 0.0;
+    let mut x = x0;
+    let mut y = y0;
+    while t < settings.max_time {
+        if let Some(i) = attractors
+            .iter()
+            .position(|(ax, ay)| (x - ax).hypot(y - ay) < settings.tolerance)
+        {
+            return Fate::Attractor(i);
+        }
+        if !x.is_finite() || !y.is_finite() || x.hypot(y) > settings.escape_radius {
+            return Fate::Unbounded;
+        }
+        (x, y) = midpoint_method(x, y, system, settings.dt);
+        t += settings.dt;
+    }
+    Fate::Undecided
+}
+
+/// Integrates a `resolution` x `resolution` grid of initial conditions spanning
+/// `x_range` and `y_range` in parallel and records where each one ends up.
+/// The grid includes both ends of each range, so `resolution` must be at
+/// least 2.
+pub fn compute_basins(
+    system: fn() -> System2D,
+    attractors: &[(f64, f64)],
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+    resolution: usize,
+    settings: &BasinSettings,
+) -> BasinMap {
+    assert!(
+        resolution >= 2,
+        "basin grid needs at least 2 points per axis, got {}",
+        resolution
+    );
+    let axis = |(min, max): (f64, f64)| {
+        (0..resolution)
+            .map(|i| min + (max - min) * i as f64 / (resolution - 1) as f64)
+            .collect::<Vec<f64>>()
+    };
+    let x_axis = axis(x_range);
+    let y_axis = axis(y_range);
+    let fates = y_axis
+        .par_iter()
+        .map(|y| {
+            x_axis
+                .iter()
+                .map(|x| fate(system, attractors, *x, *y, settings))
+                .collect::<Vec<Fate>>()
+        })
+        .collect::<Vec<Vec<Fate>>>();
+    BasinMap {
+        x_axis,
+        y_axis,
+        fates,
+        attractors_count: attractors.len(),
+    }
+}
+
+impl BasinMap {
+    /// Grid cells with a neighbour of different fate, i.e. cells the
+    /// separatrix between basins passes through.
+    pub fn separatrix(&self) -> Vec<Vec<bool>> {
+        let rows = self.fates.len();
+        let cols = self.x_axis.len();
+        (0..rows)
+            .map(|j| {
+                (0..cols)
+                    .map(|i| {
+                        (i + 1 < cols && self.fates[j][i] != self.fates[j][i + 1])
+                            || (j + 1 < rows && self.fates[j][i] != self.fates[j + 1][i])
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Fraction of the grid that ends in each attractor, followed by the
+    /// unbounded and undecided fractions.
+    pub fn fractions(&self) -> Vec<f64> {
+        let mut counts = vec![0; self.attractors_count + 2];
+        for fate in self.fates.iter().flatten() {
+            counts[self.category(*fate)] += 1;
+        }
+        let total = counts.iter().sum::<usize>() as f64;
+        counts.iter().map(|c| *c as f64 / total).collect()
+    }
+
+    fn category(&self, fate: Fate) -> usize {
+        match fate {
+            Fate::Attractor(i) => i,
+            Fate::Unbounded => self.attractors_count,
+            Fate::Undecided => self.attractors_count + 1,
+        }
+    }
+
+    /// Colour-coded basin image with the separatrix drawn in black.
+    pub fn plot(&self, title: &str) -> Plot {
+        let palette = [
+            "#1f77b4", "#ff7f0e", "#2ca02c", "#9467bd", "#8c564b", "#e377c2",
+        ];
+        let mut colors = (0..self.attractors_count)
+            .map(|i| palette[i % palette.len()])
+            .collect::<Vec<&str>>();
+        colors.extend(["#d62728", "#bbbbbb", "#000000"]);
+        let separatrix = self.separatrix();
+        let z = self
+            .fates
+            .iter()
+            .zip(separatrix)
+            .map(|(row, boundary)| {
+                row.iter()
+                    .zip(boundary)
+                    .map(|(fate, on_boundary)| {
+                        if on_boundary {
+                            (colors.len() - 1) as f64
+                        } else {
+                            self.category(*fate) as f64
+                        }
+                    })
+                    .collect::<Vec<f64>>()
+            })
+            .collect::<Vec<Vec<f64>>>();
+        // piecewise constant colour scale, one band per category
+        let n = colors.len() as f64;
+        let color_scale = colors
+            .iter()
+            .enumerate()
+            .flat_map(|(i, c)| {
+                [
+                    ColorScaleElement(i as f64 / n, c.to_string()),
+                    ColorScaleElement((i + 1) as f64 / n, c.to_string()),
+                ]
+            })
+            .collect::<Vec<ColorScaleElement>>();
+        let trace = HeatMap::new(self.x_axis.clone(), self.y_axis.clone(), z)
+            .color_scale(ColorScale::Vector(color_scale))
+            .zmin(-0.5)
+            .zmax(n - 0.5)
+            .show_scale(false);
+        let mut plot = Plot::new();
+        plot.add_trace(trace);
+        plot.set_layout(
+            Layout::new()
+                .title(Title::from(title))
+                .x_axis(Axis::new().title(Title::from("x")))
+                .y_axis(Axis::new().title(Title::from("y"))),
+        );
+        plot
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{midpoint_method, System2D};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Integrator {
+    Euler,
+    Midpoint,
+    /// Kick-drift-kick leapfrog, symplectic for x' = fx(y), y' = fy(x).
+    Leapfrog,
+}
+
+impl Integrator {
+    pub fn step(&self, x: f64, y: f64, system: fn() -> System2D, dt: f64) -> (f64, f64) {
+        match self {
+            Integrator::Euler => {
+                let (fx, fy) = system();
+                (x + dt * fx(x, y), y + dt * fy(x, y))
+            }
+            Integrator::Midpoint => midpoint_method(x, y, system, dt),
+            Integrator::Leapfrog => {
+                let (fx, fy) = system();
+                let y_half = y + 0.5 * dt * fy(x, y);
+                let next_x = x + dt * fx(x, y_half);
+                (next_x, y_half + 0.5 * dt * fy(next_x, y_half))
+            }
+        }
+    }
+
+    /// Theoretical order of accuracy of the global error.
+    pub fn expected_order(&self) -> f64 {
+        match self {
+            Integrator::Euler => 1.0,
+            Integrator::Midpoint | Integrator::Leapfrog => 2.0,
+        }
+    }
+
+    /// State at `t_end` reached with steps of `dt` (the last step is shortened
+    /// so that the integration stops exactly at `t_end`).
+    pub fn solve(
+        &self,
+        system: fn() -> System2D,
+        initial: (f64, f64),
+        t_end: f64,
+        dt: f64,
+    ) -> (f64, f64) {
+        let (mut x, mut y) = initial;
+        let full_steps = (t_end / dt).floor() as usize;
+        for _ in 0..full_steps {
+            (x, y) = self.step(x, y, system, dt);
+        }
+        let remainder = t_end - full_steps as f64 * dt;
+        if remainder > 1e-12 * dt {
+            (x, y) = self.step(x, y, system, remainder);
+        }
+        (x, y)
+    }
+}
+
+/// Solution the numerical results are compared against.
+pub enum Reference {
+    /// Exact solution (x, y) at time t for the initial condition (x0, y0).
+    Exact(fn(f64, f64, f64) -> (f64, f64)),
+    /// Numerical solution with a much smaller step.
+    Numerical { integrator: Integrator, dt: f64 },
+}
+
+pub struct ConvergenceResult {
+    pub integrator: Integrator,
+    pub dts: Vec<f64>,
+    pub errors: Vec<f64>,
+    /// Empirical order of accuracy, the slope of log(error) against log(dt).
+    pub order: f64,
+}
+
+/// Least-squares slope of log(error) against log(dt).
+pub fn fit_order(dts: &[f64], errors: &[f64]) -> f64 {
+    let points = dts
+        .iter()
+        .zip(errors)
+        .filter(|(_, e)| **e > 0.0)
+        .map(|(dt, e)| (dt.ln(), e.ln()))
+        .collect::<Vec<(f64, f64)>>();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum::<f64>();
+    let variance = points
+        .iter()
+        .map(|(x, _)| (x - mean_x).powi(2))
+        .sum::<f64>();
+    covariance / variance
+}
+
+/// Measures the global error at `t_end` of every integrator for every step
+/// in `dts` and fits the empirical order of accuracy.
+pub fn convergence_study(
+    system: fn() -> System2D,
+    initial: (f64, f64),
+    t_end: f64,
+    dts: &[f64],
+    integrators: &[Integrator],
+    reference: &Reference,
+) -> Vec<ConvergenceResult> {
+    let (x_ref, y_ref) = match reference {
+        Reference::Exact(solution) => solution(t_end, initial.0, initial.1),
+        Reference::Numerical { integrator, dt } => integrator.solve(system, initial, t_end, *dt),
+    };
+    integrators
+        .iter()
+        .map(|integrator| {
+            let errors = dts
+                .iter()
+                .map(|dt| {
+                    let (x, y) = integrator.solve(system, initial, t_end, *dt);
+                    (x - x_ref).hypot(y - y_ref)
+                })
+                .collect::<Vec<f64>>();
+            ConvergenceResult {
+                integrator: *integrator,
+                dts: dts.to_vec(),
+                order: fit_order(dts, &errors),
+                errors,
+            }
+        })
+        .collect()
+}
+
+/// Markdown table of the errors for every dt, followed by the fitted orders.
+pub fn format_table(results: &[ConvergenceResult]) -> String {
+    let mut table = String::from("| dt |");
+    for result in results {
+        table.push_str(&format!(" {:?} error |", result.integrator));
+    }
+    table.push_str("\n|---|");
+    table.push_str(&"---|".repeat(results.len()));
+    table.push('\n');
+    for (i, dt) in results[0].dts.iter().enumerate() {
+        table.push_str(&format!("| {} |", dt));
+        for result in results {
+            table.push_str(&format!(" {:.3e} |", result.errors[i]));
+        }
+        table.push('\n');
+    }
+    table.push_str("| order |");
+    for result in results {
+        table.push_str(&format!(
+            " {:.3} (expected {}) |",
+            result.order,
+            result.integrator.expected_order()
+        ));
+    }
+    table.push('\n');
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{system_1, system_task_4};
+
+    const DTS: [f64; 5] = [0.1, 0.05, 0.02, 0.01, 0.005];
+
+    fn orders(system: fn() -> System2D, initial: (f64, f64), reference: &Reference) -> Vec<f64> {
+        let integrators = [Integrator::Euler, Integrator::Midpoint];
+        convergence_study(system, initial, 5.0, &DTS, &integrators, reference)
+            .iter()
+            .map(|result| result.order)
+            .collect()
+    }
+
+    #[test]
+    fn fit_order_recovers_power_law() {
+        let errors = DTS.iter().map(|dt| 3.0 * dt.powi(2)).collect::<Vec<f64>>();
+        assert!((fit_order(&DTS, &errors) - 2.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn harmonic_oscillator_orders() {
+        let exact = Reference::Exact(|t, x0, y0| {
+            (x0 * t.cos() + y0 * t.sin(), -x0 * t.sin() + y0 * t.cos())
+        });
+        let orders = orders(system_1, (1.0, 0.0), &exact);
+        assert!((orders[0] - 1.0).abs() < 0.1, "Euler order = {}", orders[0]);
+        assert!(
+            (orders[1] - 2.0).abs() < 0.1,
+            "midpoint order = {}",
+            orders[1]
+        );
+    }
+
+    #[test]
+    fn nonlinear_system_orders() {
+        let fine = Reference::Numerical {
+            integrator: Integrator::Midpoint,
+            dt: 1e-5,
+        };
+        let orders = orders(system_task_4, (1.0, 2.0), &fine);
+        assert!((orders[0] - 1.0).abs() < 0.1, "Euler order = {}", orders[0]);
+        assert!(
+            (orders[1] - 2.0).abs() < 0.1,
+            "midpoint order = {}",
+            orders[1]
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use nalgebra::{DMatrix, DVector};
+
+/// Right-hand side x' = f(x) of an autonomous flow, or the update x -> f(x) of a map.
+pub type VectorField = fn(&DVector<f64>) -> DVector<f64>;
+
+/// Jacobian matrix Df(x) of a `VectorField`.
+pub type Jacobian = fn(&DVector<f64>) -> DMatrix<f64>;
+
+/// One midpoint (second order Runge-Kutta) step of x' = f(x) in any dimension.
+pub fn midpoint_step(f: VectorField, x: &DVector<f64>, dt: f64) -> DVector<f64> {
+    let k = dt * f(x);
+    x + dt * f(&(x + 0.5 * k))
+}
+
+/// Midpoint step of the flow together with its tangent dynamics q' = Df(x) q,
+/// so that the columns of `q` follow infinitesimal perturbations of `x`.
+pub fn midpoint_tangent_step(
+    f: VectorField,
+    jacobian: Jacobian,
+    x: &DVector<f64>,
+    q: &DMatrix<f64>,
+    dt: f64,
+) -> (DVector<f64>, DMatrix<f64>) {
+    let kx = dt * f(x);
+    let kq = dt * jacobian(x) * q;
+    let x_mid = x + 0.5 * kx;
+    let q_mid = q + 0.5 * kq;
+    let next_x = x + dt * f(&x_mid);
+    let next_q = q + dt * jacobian(&x_mid) * q_mid;
+    (next_x, next_q)
+}
+
+/// Lorenz system with the classic parameters sigma = 10, rho = 28, beta = 8/3.
+pub fn lorenz(x: &DVector<f64>) -> DVector<f64> {
+    let (sigma, rho, beta) = (10.0, 28.0, 8.0 / 3.0);
+    DVector::from_vec(vec![
+        sigma * (x[1] - x[0]),
+        x[0] * (rho - x[2]) - x[1],
+        x[0] * x[1] - beta * x[2],
+    ])
+}
+
+pub fn lorenz_jacobian(x: &DVector<f64>) -> DMatrix<f64> {
+    let (sigma, rho, beta) = (10.0, 28.0, 8.0 / 3.0);
+    DMatrix::from_row_slice(
+        3,
+        3,
+        &[
+            -sigma,
+            sigma,
+            0.0,
+            rho - x[2],
+            -1.0,
+            -x[0],
+            x[1],
+            x[0],
+            -beta,
+        ],
+    )
+}
+
+/// Henon map with the classic parameters a = 1.4, b = 0.3.
+pub fn henon(x: &DVector<f64>) -> DVector<f64> {
+    let (a, b) = (1.4, 0.3);
+    DVector::from_vec(vec![1.0 - a * x[0] * x[0] + x[1], b * x[0]])
+}
+
+pub fn henon_jacobian(x: &DVector<f64>) -> DMatrix<f64> {
+    let (a, b) = (1.4, 0.3);
+    DMatrix::from_row_slice(2, 2, &[-2.0 * a * x[0], 1.0, b, 0.0])
+}
+
+/// Driven damped pendulum theta'' + theta'/q + sin(theta) = g cos(omega t) with
+/// q = 2, g = 1.5, omega = 2/3, made autonomous by the driving phase
+/// x = (theta, theta', omega t).
+pub fn driven_pendulum(x: &DVector<f64>) -> DVector<f64> {
+    let (q, g, omega) = (2.0, 1.5, 2.0 / 3.0);
+    DVector::from_vec(vec![x[1], -x[1] / q - x[0].sin() + g * x[2].cos(), omega])
+}
+
+/// Forced Duffing oscillator x'' + delta x' + alpha x + beta x^3 = gamma cos(omega t)
+/// with delta = 0.3, alpha = -1, beta = 1, gamma = 0.5, omega = 1.2, made
+/// autonomous by the driving phase x = (x, x', omega t).
+pub fn duffing(x: &DVector<f64>) -> DVector<f64> {
+    let (delta, alpha, beta, gamma, omega) = (0.3, -1.0, 1.0, 0.5, 1.2);
+    DVector::from_vec(vec![
+        x[1],
+        -delta * x[1] - alpha * x[0] - beta * x[0].powi(3) + gamma * x[2].cos(),
+        omega,
+    ])
+}
+
+/// Maps an angle to the interval [-pi, pi).
+pub fn wrap_angle(angle: f64) -> f64 {
+    (angle + std::f64::consts::PI).rem_euclid(2.0 * std::f64::consts::PI) - std::f64::consts::PI
+}
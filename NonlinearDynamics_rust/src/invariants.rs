@@ -0,0 +1,94 @@
+use crate::convergence::Integrator;
+use crate::System2D;
+
+/// Quantity conserved by the exact flow, e.g. energy, evaluated at (x, y).
+#[derive(Clone, Copy)]
+pub struct Invariant {
+    pub name: &'static str,
+    pub value: fn(f64, f64) -> f64,
+}
+
+/// System together with the invariants its exact solutions conserve.
+pub struct ConservativeSystem {
+    pub name: &'static str,
+    pub system: fn() -> System2D,
+    pub invariants: Vec<Invariant>,
+}
+
+/// Time series of I(t) - I(0) along a numerical trajectory.
+pub struct DriftSeries {
+    pub t_axis: Vec<f64>,
+    pub drift: Vec<f64>,
+}
+
+#[derive(Debug)]
+pub struct DriftStatistics {
+    pub max_abs: f64,
+    pub mean_abs: f64,
+    pub rms: f64,
+    /// Slope of a linear fit of the drift in time; close to zero when the
+    /// error oscillates (symplectic schemes) and finite for secular drift.
+    pub secular_rate: f64,
+}
+
+impl ConservativeSystem {
+    /// Integrates from `initial` until `t_end` and records the drift of every
+    /// attached invariant, in the order of `self.invariants`.
+    pub fn drift(
+        &self,
+        integrator: Integrator,
+        initial: (f64, f64),
+        dt: f64,
+        t_end: f64,
+    ) -> Vec<DriftSeries> {
+        let (mut x, mut y) = initial;
+        let initial_values = self
+            .invariants
+            .iter()
+            .map(|inv| (inv.value)(x, y))
+            .collect::<Vec<f64>>();
+        let mut series = self
+            .invariants
+            .iter()
+            .map(|_| DriftSeries {
+                t_axis: vec![0.0],
+                drift: vec![0.0],
+            })
+            .collect::<Vec<DriftSeries>>();
+        let steps = (t_end / dt).round() as usize;
+        for n in 1..=steps {
+            (x, y) = integrator.step(x, y, self.system, dt);
+            let t = n as f64 * dt;
+            for ((inv, s), i0) in self.invariants.iter().zip(&mut series).zip(&initial_values) {
+                s.t_axis.push(t);
+                s.drift.push((inv.value)(x, y) - i0);
+            }
+        }
+        series
+    }
+}
+
+impl DriftSeries {
+    pub fn statistics(&self) -> DriftStatistics {
+        let n = self.drift.len() as f64;
+        let mean_t = self.t_axis.iter().sum::<f64>() / n;
+        let mean_drift = self.drift.iter().sum::<f64>() / n;
+        let covariance = self
+            .t_axis
+            .iter()
+            .zip(&self.drift)
+            .map(|(t, d)| (t - mean_t) * (d - mean_drift))
+            .sum::<f64>();
+        let variance = self
+            .t_axis
+            .iter()
+            .map(|t| (t - mean_t).powi(2))
+            .sum::<f64>();
+        DriftStatistics {
+            max_abs: self.drift.iter().fold(0.0, |m, d| m.max(d.abs())),
+            mean_abs: self.drift.iter().map(|d| d.abs()).sum::<f64>() / n,
+            rms: (self.drift.iter().map(|d| d * d).sum::<f64>() / n).sqrt(),
+            secular_rate: covariance / variance,
+        }
+    }
+}
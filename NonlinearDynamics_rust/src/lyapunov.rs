@@ -0,0 +1,199 @@
+use nalgebra::{DMatrix, DVector};
+
+use crate::flows::{midpoint_step, midpoint_tangent_step, Jacobian, VectorField};
+
+/// Dynamical system whose Lyapunov exponents are measured.
+#[derive(Clone, Copy)]
+pub enum Dynamics {
+    /// Continuous flow x' = f(x), integrated with the midpoint method.
+    Flow {
+        f: VectorField,
+        jacobian: Jacobian,
+        dt: f64,
+    },
+    /// Discrete map x_{n+1} = f(x_n).
+    Map { f: VectorField, jacobian: Jacobian },
+}
+
+impl Dynamics {
+    fn step(&self, x: &DVector<f64>) -> DVector<f64> {
+        match *self {
+            Dynamics::Flow { f, dt, .. } => midpoint_step(f, x, dt),
+            Dynamics::Map { f, .. } => f(x),
+        }
+    }
+
+    fn tangent_step(&self, x: &DVector<f64>, q: &DMatrix<f64>) -> (DVector<f64>, DMatrix<f64>) {
+        match *self {
+            Dynamics::Flow { f, jacobian, dt } => midpoint_tangent_step(f, jacobian, x, q, dt),
+            Dynamics::Map { f, jacobian } => (f(x), jacobian(x) * q),
+        }
+    }
+
+    /// Time elapsed in one step: dt for flows, one iteration for maps.
+    fn time_step(&self) -> f64 {
+        match *self {
+            Dynamics::Flow { dt, .. } => dt,
+            Dynamics::Map { .. } => 1.0,
+        }
+    }
+}
+
+pub struct LyapunovSettings {
+    /// Steps discarded before measuring, so the orbit settles on the attractor.
+    pub transient_steps: usize,
+    /// Steps over which the exponents are averaged.
+    pub steps: usize,
+    /// Number of steps between renormalisations (or QR decompositions).
+    pub renormalise_every: usize,
+    /// Initial separation of the two trajectories in `largest_exponent`.
+    pub d0: f64,
+}
+
+impl Default for LyapunovSettings {
+    fn default() -> Self {
+        LyapunovSettings {
+            transient_steps: 10_000,
+            steps: 200_000,
+            renormalise_every: 10,
+            d0: 1e-8,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AttractorType {
+    /// All exponents negative: stable equilibrium (or periodic orbit of a map).
+    Stable,
+    /// One zero exponent, the rest negative.
+    LimitCycle,
+    /// Two or more zero exponents, the rest negative.
+    QuasiPeriodic,
+    /// Exactly one positive exponent.
+    Chaotic,
+    /// More than one positive exponent.
+    Hyperchaotic,
+}
+
+fn settle(dynamics: &Dynamics, x0: &DVector<f64>, settings: &LyapunovSettings) -> DVector<f64> {
+    let mut x = x0.clone();
+    for _ in 0..settings.transient_steps {
+        x = dynamics.step(&x);
+    }
+    x
+}
+
+/// Largest Lyapunov exponent from the separation of two nearby trajectories,
+/// rescaled back to `d0` every `renormalise_every` steps (Benettin method).
+pub fn largest_exponent(
+    dynamics: &Dynamics,
+    x0: &DVector<f64>,
+    settings: &LyapunovSettings,
+) -> f64 {
+    let mut x = settle(dynamics, x0, settings);
+    let direction = DVector::from_element(x.len(), 1.0).normalize();
+    let mut y = &x + settings.d0 * direction;
+    let mut log_sum = 0.0;
+    let mut renormalisations = 0;
+    for n in 1..=settings.steps {
+        x = dynamics.step(&x);
+        y = dynamics.step(&y);
+        if n % settings.renormalise_every == 0 {
+            let d = (&y - &x).norm();
+            log_sum += (d / settings.d0).ln();
+            y = &x + (&y - &x) * (settings.d0 / d);
+            renormalisations += 1;
+        }
+    }
+    let elapsed = (renormalisations * settings.renormalise_every) as f64 * dynamics.time_step();
+    log_sum / elapsed
+}
+
+/// Full Lyapunov spectrum, sorted in decreasing order, obtained by evolving an
+/// orthonormal frame in tangent space and re-orthonormalising it with a QR
+/// decomposition every `renormalise_every` steps.
+pub fn spectrum(dynamics: &Dynamics, x0: &DVector<f64>, settings: &LyapunovSettings) -> Vec<f64> {
+    let mut x = settle(dynamics, x0, settings);
+    let dim = x.len();
+    let mut q = DMatrix::<f64>::identity(dim, dim);
+    let mut log_sums = vec![0.0; dim];
+    let mut renormalisations = 0;
+    for n in 1..=settings.steps {
+        let (next_x, next_q) = dynamics.tangent_step(&x, &q);
+        x = next_x;
+        q = next_q;
+        if n % settings.renormalise_every == 0 {
+            let qr = q.qr();
+            let r = qr.r();
+            for (i, log_sum) in log_sums.iter_mut().enumerate() {
+                *log_sum += r[(i, i)].abs().ln();
+            }
+            q = qr.q();
+            renormalisations += 1;
+        }
+    }
+    let elapsed = (renormalisations * settings.renormalise_every) as f64 * dynamics.time_step();
+    let mut exponents = log_sums.iter().map(|s| s / elapsed).collect::<Vec<f64>>();
+    exponents.sort_by(|a, b| b.total_cmp(a));
+    exponents
+}
+
+/// Classifies an attractor by the signs of its Lyapunov exponents; exponents
+/// with |lambda| <= `tolerance` are treated as zero.
+pub fn classify(exponents: &[f64], tolerance: f64) -> AttractorType {
+    let positive = exponents.iter().filter(|l| **l > tolerance).count();
+    let zero = exponents.iter().filter(|l| l.abs() <= tolerance).count();
+    match (positive, zero) {
+        (0, 0) => AttractorType::Stable,
+        (0, 1) => AttractorType::LimitCycle,
+        (0, _) => AttractorType::QuasiPeriodic,
+        (1, _) => AttractorType::Chaotic,
+        _ => AttractorType::Hyperchaotic,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows;
+
+    #[test]
+    fn lorenz_largest_exponent_matches_literature() {
+        let lorenz = Dynamics::Flow {
+            f: flows::lorenz,
+            jacobian: flows::lorenz_jacobian,
+            dt: 0.005,
+        };
+        let x0 = DVector::from_vec(vec![1.0, 1.0, 1.0]);
+        let settings = LyapunovSettings::default();
+        let largest = largest_exponent(&lorenz, &x0, &settings);
+        assert!((largest - 0.906).abs() < 0.05, "lambda_1 = {}", largest);
+        let exponents = spectrum(&lorenz, &x0, &settings);
+        assert!(
+            (exponents[0] - 0.906).abs() < 0.05,
+            "spectrum = {:?}",
+            exponents
+        );
+        assert_eq!(classify(&exponents, 0.05), AttractorType::Chaotic);
+    }
+
+    #[test]
+    fn henon_largest_exponent_matches_literature() {
+        let henon = Dynamics::Map {
+            f: flows::henon,
+            jacobian: flows::henon_jacobian,
+        };
+        let x0 = DVector::from_vec(vec![0.1, 0.1]);
+        let settings = LyapunovSettings::default();
+        let largest = largest_exponent(&henon, &x0, &settings);
+        assert!((largest - 0.419).abs() < 0.01, "lambda_1 = {}", largest);
+        let exponents = spectrum(&henon, &x0, &settings);
+        assert!(
+            (exponents[0] - 0.419).abs() < 0.01,
+            "spectrum = {:?}",
+            exponents
+        );
+        // the exponents of the Henon map sum to ln(b) = ln(0.3)
+        assert!((exponents.iter().sum::<f64>() - 0.3_f64.ln()).abs() < 1e-6);
+    }
+}
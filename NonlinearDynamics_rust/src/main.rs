@@ -1,11 +1,23 @@
 use std::vec;
 
-use nalgebra::{Matrix2, Vector2};
-use plotly::color::{Color, Rgb};
-use plotly::common::{ColorScale, Line, Mode, Title};
-use plotly::layout::{Axis, Layout};
+use nalgebra::{DVector, Matrix2, Vector2};
+use plotly::color::Rgb;
+use plotly::common::{Line, Mode, Title};
+use plotly::layout::{Axis, AxisType, Layout};
 use plotly::{color, Plot, Scatter};
 
+mod basins;
+mod convergence;
+mod flows;
+mod invariants;
+mod lyapunov;
+mod poincare;
+
+use lyapunov::{Dynamics, LyapunovSettings};
+
+/// Right-hand sides (x', y') of a planar system x' = fx(x, y), y' = fy(x, y).
+type System2D = (fn(f64, f64) -> f64, fn(f64, f64) -> f64);
+
 struct Solution {
     x_0: f64,
     t_axis: Vec<f64>,
@@ -21,7 +33,7 @@ fn function(x: f64) -> f64 {
 }
 
 fn task_1() {
-    let dtvec = vec![0.1, 0.01, 0.001, 0.0001];
+    let dtvec = [0.1, 0.01, 0.001, 0.0001];
     let x_0vec = vec![-0.1, 0.1, 0.9, 1.1, 1.9, 2.1];
 
     for dt in dtvec {
@@ -39,9 +51,9 @@ fn task_1() {
                 x_axis.push(x);
             }
             let solution = Solution {
-                x_0: x_0,
-                t_axis: t_axis,
-                x_axis: x_axis,
+                x_0,
+                t_axis,
+                x_axis,
             };
             solutions.push(solution);
         }
@@ -63,8 +75,8 @@ fn task_1() {
 }
 
 fn task_2() {
-    let systems = vec![system_1, system_2, system_3, system_4];
-    let systems_names = vec!["x''-x=0", "x''+sin(x)=0", "x''+x-x^3=0", "x''-x+x^3=0"];
+    let systems = [system_1, system_2, system_3, system_4];
+    let systems_names = ["x''-x=0", "x''+sin(x)=0", "x''+x-x^3=0", "x''-x+x^3=0"];
     let dt = 0.1;
     // make fucntion to make initial condidiotns for grid from -10, -10 to 10, 10
     let initial_conditions = (-10..=10)
@@ -98,36 +110,31 @@ fn task_2() {
     }
 }
 
-fn system_1() -> (fn(f64, f64) -> f64, fn(f64, f64) -> f64) {
-    let fx: fn(f64, f64) -> f64 = |x, y| y;
-    let fy: fn(f64, f64) -> f64 = |x, y| -x;
+fn system_1() -> System2D {
+    let fx: fn(f64, f64) -> f64 = |_x, y| y;
+    let fy: fn(f64, f64) -> f64 = |x, _y| -x;
     (fx, fy)
 }
 
-fn system_2() -> (fn(f64, f64) -> f64, fn(f64, f64) -> f64) {
-    let fx: fn(f64, f64) -> f64 = |x, y| y;
-    let fy: fn(f64, f64) -> f64 = |x, y| -x.sin();
+fn system_2() -> System2D {
+    let fx: fn(f64, f64) -> f64 = |_x, y| y;
+    let fy: fn(f64, f64) -> f64 = |x, _y| -x.sin();
     (fx, fy)
 }
 
-fn system_3() -> (fn(f64, f64) -> f64, fn(f64, f64) -> f64) {
-    let fx: fn(f64, f64) -> f64 = |x, y| y;
-    let fy: fn(f64, f64) -> f64 = |x, y| -x + x.powi(3);
+fn system_3() -> System2D {
+    let fx: fn(f64, f64) -> f64 = |_x, y| y;
+    let fy: fn(f64, f64) -> f64 = |x, _y| -x + x.powi(3);
     (fx, fy)
 }
 
-fn system_4() -> (fn(f64, f64) -> f64, fn(f64, f64) -> f64) {
-    let fx: fn(f64, f64) -> f64 = |x, y| y;
-    let fy: fn(f64, f64) -> f64 = |x, y| x - x.powi(3);
+fn system_4() -> System2D {
+    let fx: fn(f64, f64) -> f64 = |_x, y| y;
+    let fy: fn(f64, f64) -> f64 = |x, _y| x - x.powi(3);
     (fx, fy)
 }
 
-fn midpoint_method(
-    xn: f64,
-    yn: f64,
-    f: fn() -> (fn(f64, f64) -> f64, fn(f64, f64) -> f64),
-    dt: f64,
-) -> (f64, f64) {
+fn midpoint_method(xn: f64, yn: f64, f: fn() -> System2D, dt: f64) -> (f64, f64) {
     let (fx, fy) = f();
     let kx = dt * fx(xn, yn);
     let ky = dt * fy(xn, yn);
@@ -137,7 +144,6 @@ fn midpoint_method(
 }
 
 fn task_3() {
-    let mut sys_num = 1;
     let t_range = 4.0;
     let initial_con_range = 6;
     let dt = 0.1;
@@ -165,7 +171,7 @@ fn task_3() {
 
     draw_state_graph(a_matrixies.clone());
 
-    for A in a_matrixies {
+    for (sys_num, a) in (1..).zip(a_matrixies) {
         let mut plot = Plot::new();
         for condition in initial_conditions.clone() {
             let mut t = 0.0;
@@ -173,7 +179,7 @@ fn task_3() {
             let mut x1_axis = vec![x[0]];
             let mut x2_axis = vec![x[1]];
             while t < t_range {
-                x = linear_midpoint_method(A, x, dt);
+                x = linear_midpoint_method(a, x, dt);
                 t += dt;
                 x1_axis.push(x[0]);
                 x2_axis.push(x[1]);
@@ -197,7 +203,6 @@ fn task_3() {
         plot.set_layout(layout);
         //plot.show();
         plot.show_image(plotly::ImageFormat::JPEG, 1000, 800);
-        sys_num += 1;
     }
 }
 
@@ -212,29 +217,27 @@ fn draw_state_graph(
     >,
 ) {
     // Draw parabole
-    // make T_axis as range from -10 to 10 with step 0.1
-    let T_axis = (-100..=100).map(|T| T as f32 * 0.1).collect::<Vec<f32>>();
-    let D_curve = T_axis
+    // make t_axis as range from -10 to 10 with step 0.1
+    let t_axis = (-100..=100).map(|t| t as f32 * 0.1).collect::<Vec<f32>>();
+    let d_curve = t_axis
         .clone()
         .into_iter()
-        .map(|T| T * T / 4.0)
+        .map(|t| t * t / 4.0)
         .collect::<Vec<f32>>();
     let mut plot = Plot::new();
-    let trace = Scatter::new(T_axis, D_curve)
+    let trace = Scatter::new(t_axis, d_curve)
         .mode(Mode::Lines)
         .name("D=T^2/4")
         .line(Line::new().color(color::NamedColor::Red));
     plot.add_trace(trace);
     // Draw each system position
-    let mut sys_num = 1;
-    for A in a_matrixies {
-        let trace = A.trace();
-        let det = A.determinant();
+    for (sys_num, a) in (1..).zip(a_matrixies) {
+        let trace = a.trace();
+        let det = a.determinant();
         let trace = Scatter::new(vec![trace], vec![det])
             .mode(Mode::Markers)
             .name(format!("System = {}", sys_num));
         plot.add_trace(trace);
-        sys_num += 1;
     }
     let layout = Layout::new()
         .title(Title::from("System position"))
@@ -312,15 +315,286 @@ fn task_4() {
     plot.show_image(plotly::ImageFormat::JPEG, 1000, 800);
 }
 
-fn system_task_4() -> (fn(f64, f64) -> f64, fn(f64, f64) -> f64) {
+fn system_task_4() -> System2D {
     let dx: fn(f64, f64) -> f64 = |x, y| x * (3.0 - x - 2.0 * y);
     let dy: fn(f64, f64) -> f64 = |x, y| y * (2.0 - x - y);
     (dx, dy)
 }
 
+fn task_5() {
+    let lorenz = Dynamics::Flow {
+        f: flows::lorenz,
+        jacobian: flows::lorenz_jacobian,
+        dt: 0.005,
+    };
+    let henon = Dynamics::Map {
+        f: flows::henon,
+        jacobian: flows::henon_jacobian,
+    };
+    // literature values of the spectra used to validate the estimates
+    let cases = [
+        (
+            "Lorenz",
+            lorenz,
+            DVector::from_vec(vec![1.0, 1.0, 1.0]),
+            vec![0.906, 0.0, -14.572],
+        ),
+        (
+            "Henon",
+            henon,
+            DVector::from_vec(vec![0.1, 0.1]),
+            vec![0.419, -1.624],
+        ),
+    ];
+    let settings = LyapunovSettings::default();
+    for (name, dynamics, x0, expected) in cases {
+        let largest = lyapunov::largest_exponent(&dynamics, &x0, &settings);
+        let exponents = lyapunov::spectrum(&dynamics, &x0, &settings);
+        println!(
+            "{}: largest exponent = {:.4} (expected {})",
+            name, largest, expected[0]
+        );
+        println!(
+            "{}: spectrum = {:.4?} (expected {:?})",
+            name, exponents, expected
+        );
+        println!("{}: {:?}", name, lyapunov::classify(&exponents, 0.01));
+    }
+}
+
+fn task_6() {
+    std::fs::create_dir_all("results").expect("Could not create results directory");
+    let dt = 0.01;
+    let section = poincare::stroboscopic_section();
+    let systems: [(&str, flows::VectorField, bool); 2] = [
+        ("pendulum", flows::driven_pendulum, true),
+        ("duffing", flows::duffing, false),
+    ];
+    for (name, f, is_angle) in systems {
+        let x0 = DVector::from_vec(vec![0.1, 0.0, 0.0]);
+        let mut points = poincare::section_points(f, &section, &x0, dt, 100.0, 5000);
+        if is_angle {
+            points
+                .iter_mut()
+                .for_each(|p| p[0] = flows::wrap_angle(p[0]));
+        }
+        let return_map = poincare::first_return_map(&points, 0);
+        poincare::save_section(&points, &format!("results/{}_section.csv", name)).unwrap();
+        poincare::save_return_map(&return_map, &format!("results/{}_return_map.csv", name))
+            .unwrap();
+
+        let mut plot = Plot::new();
+        let trace = Scatter::new(
+            points.iter().map(|p| p[0]).collect::<Vec<f64>>(),
+            points.iter().map(|p| p[1]).collect::<Vec<f64>>(),
+        )
+        .mode(Mode::Markers)
+        .name("section");
+        plot.add_trace(trace);
+        plot.set_layout(
+            Layout::new()
+                .title(Title::from(format!("Poincare section: {}", name).as_str()))
+                .x_axis(Axis::new().title(Title::from("x")))
+                .y_axis(Axis::new().title(Title::from("x'"))),
+        );
+        plot.show_image(plotly::ImageFormat::JPEG, 1000, 800);
+
+        let mut plot = Plot::new();
+        let (s_n, s_next): (Vec<f64>, Vec<f64>) = return_map.into_iter().unzip();
+        let trace = Scatter::new(s_n, s_next)
+            .mode(Mode::Markers)
+            .name("return map");
+        plot.add_trace(trace);
+        plot.set_layout(
+            Layout::new()
+                .title(Title::from(format!("First-return map: {}", name).as_str()))
+                .x_axis(Axis::new().title(Title::from("x_n")))
+                .y_axis(Axis::new().title(Title::from("x_n+1"))),
+        );
+        plot.show_image(plotly::ImageFormat::JPEG, 1000, 800);
+    }
+
+    // Lorenz map: successive maxima of z, found where z' changes sign from + to -
+    let maxima = poincare::Section {
+        surface: |x| flows::lorenz(x)[2],
+        crossing: poincare::Crossing::Downward,
+    };
+    let x0 = DVector::from_vec(vec![1.0, 1.0, 1.0]);
+    let points = poincare::section_points(flows::lorenz, &maxima, &x0, 0.005, 50.0, 2000);
+    let return_map = poincare::first_return_map(&points, 2);
+    poincare::save_return_map(&return_map, "results/lorenz_return_map.csv").unwrap();
+    let mut plot = Plot::new();
+    let (z_n, z_next): (Vec<f64>, Vec<f64>) = return_map.into_iter().unzip();
+    plot.add_trace(
+        Scatter::new(z_n, z_next)
+            .mode(Mode::Markers)
+            .name("z maxima"),
+    );
+    plot.set_layout(
+        Layout::new()
+            .title(Title::from("Lorenz map"))
+            .x_axis(Axis::new().title(Title::from("z_n")))
+            .y_axis(Axis::new().title(Title::from("z_n+1"))),
+    );
+    plot.show_image(plotly::ImageFormat::JPEG, 1000, 800);
+}
+
+fn task_7() {
+    // stable equilibria of the competition model, (0, 0) and (1, 1) are unstable
+    let attractors = [(3.0, 0.0), (0.0, 2.0)];
+    let basins = basins::compute_basins(
+        system_task_4,
+        &attractors,
+        (0.0, 20.0),
+        (0.0, 20.0),
+        400,
+        &basins::BasinSettings::default(),
+    );
+    let fractions = basins.fractions();
+    for (i, attractor) in attractors.iter().enumerate() {
+        println!("basin of {:?}: {:.3}", attractor, fractions[i]);
+    }
+    println!(
+        "unbounded: {:.3}, undecided: {:.3}",
+        fractions[attractors.len()],
+        fractions[attractors.len() + 1]
+    );
+    let plot = basins.plot("Basins of attraction of the competition model");
+    plot.show_image(plotly::ImageFormat::JPEG, 1000, 1000);
+}
+
+fn system_task_1() -> System2D {
+    let fx: fn(f64, f64) -> f64 = |x, _y| function(x);
+    let fy: fn(f64, f64) -> f64 = |_x, _y| 0.0;
+    (fx, fy)
+}
+
+fn task_8() {
+    use convergence::{Integrator, Reference};
+    let dts = [0.1, 0.05, 0.02, 0.01, 0.005, 0.002, 0.001];
+    let integrators = [Integrator::Euler, Integrator::Midpoint];
+    // x'' = -x has the exact solution x = x0 cos(t) + y0 sin(t)
+    let harmonic =
+        Reference::Exact(|t, x0, y0| (x0 * t.cos() + y0 * t.sin(), -x0 * t.sin() + y0 * t.cos()));
+    let fine = Reference::Numerical {
+        integrator: Integrator::Midpoint,
+        dt: 1e-5,
+    };
+    let cases = [
+        (
+            "x''+x=0",
+            system_1 as fn() -> System2D,
+            (1.0, 0.0),
+            &harmonic,
+        ),
+        ("x'=x(x-1)(x-2)", system_task_1, (0.9, 0.0), &fine),
+        ("competition model", system_task_4, (1.0, 2.0), &fine),
+    ];
+    for (name, system, initial, reference) in cases {
+        let results =
+            convergence::convergence_study(system, initial, 5.0, &dts, &integrators, reference);
+        println!("{}\n{}", name, convergence::format_table(&results));
+    }
+}
+
+fn task_9() {
+    use convergence::Integrator;
+    use invariants::{ConservativeSystem, Invariant};
+    let systems = [
+        ConservativeSystem {
+            name: "x''+x=0",
+            system: system_1,
+            invariants: vec![Invariant {
+                name: "energy",
+                value: |x, y| 0.5 * y * y + 0.5 * x * x,
+            }],
+        },
+        ConservativeSystem {
+            name: "x''+sin(x)=0",
+            system: system_2,
+            invariants: vec![Invariant {
+                name: "energy",
+                value: |x, y| 0.5 * y * y - x.cos(),
+            }],
+        },
+        ConservativeSystem {
+            name: "x''+x-x^3=0",
+            system: system_3,
+            invariants: vec![Invariant {
+                name: "energy",
+                value: |x, y| 0.5 * y * y + 0.5 * x * x - 0.25 * x.powi(4),
+            }],
+        },
+        ConservativeSystem {
+            name: "x''-x+x^3=0",
+            system: system_4,
+            invariants: vec![Invariant {
+                name: "energy",
+                value: |x, y| 0.5 * y * y - 0.5 * x * x + 0.25 * x.powi(4),
+            }],
+        },
+    ];
+    let integrators = [
+        Integrator::Euler,
+        Integrator::Midpoint,
+        Integrator::Leapfrog,
+    ];
+    let dt = 0.1;
+    for system in systems {
+        let mut plots = system
+            .invariants
+            .iter()
+            .map(|_| Plot::new())
+            .collect::<Vec<Plot>>();
+        for integrator in integrators {
+            let series = system.drift(integrator, (0.5, 0.0), dt, 200.0);
+            for ((invariant, s), plot) in system.invariants.iter().zip(series).zip(&mut plots) {
+                let stats = s.statistics();
+                println!(
+                    "{} {} {:?}: max |drift| = {:.3e}, mean |drift| = {:.3e}, rms = {:.3e}, secular rate = {:.3e}",
+                    system.name,
+                    invariant.name,
+                    integrator,
+                    stats.max_abs,
+                    stats.mean_abs,
+                    stats.rms,
+                    stats.secular_rate
+                );
+                // |drift| on a log axis, skipping t = 0 where it vanishes
+                let trace = Scatter::new(
+                    s.t_axis[1..].to_vec(),
+                    s.drift[1..].iter().map(|d| d.abs()).collect::<Vec<f64>>(),
+                )
+                .mode(Mode::Lines)
+                .name(format!("{:?}", integrator));
+                plot.add_trace(trace);
+            }
+        }
+        for (invariant, mut plot) in system.invariants.iter().zip(plots) {
+            let layout = Layout::new()
+                .title(Title::from(
+                    format!("{} drift: {}, dt={}", invariant.name, system.name, dt).as_str(),
+                ))
+                .x_axis(Axis::new().title(Title::from("t")))
+                .y_axis(
+                    Axis::new()
+                        .title(Title::from("|I(t) - I(0)|"))
+                        .type_(AxisType::Log),
+                );
+            plot.set_layout(layout);
+            plot.show_image(plotly::ImageFormat::JPEG, 1000, 800);
+        }
+    }
+}
+
 fn main() {
     task_1();
     task_2();
     task_3();
     task_4();
+    task_5();
+    task_6();
+    task_7();
+    task_8();
+    task_9();
 }
@@ -0,0 +1,121 @@
+use std::error::Error;
+
+use csv::Writer;
+use nalgebra::DVector;
+
+use crate::flows::{midpoint_step, VectorField};
+
+/// Direction in which a trajectory has to pierce the section to be recorded.
+#[derive(Clone, Copy)]
+pub enum Crossing {
+    /// g(x) goes from negative to positive.
+    Upward,
+    /// g(x) goes from positive to negative.
+    Downward,
+}
+
+/// Hypersurface g(x) = 0 in phase space.
+#[derive(Clone, Copy)]
+pub struct Section {
+    pub surface: fn(&DVector<f64>) -> f64,
+    pub crossing: Crossing,
+}
+
+impl Section {
+    fn is_crossed(&self, g_prev: f64, g_next: f64) -> bool {
+        let upward = g_prev < 0.0 && g_next >= 0.0;
+        let downward = g_prev > 0.0 && g_next <= 0.0;
+        match self.crossing {
+            Crossing::Upward => upward,
+            Crossing::Downward => downward,
+        }
+    }
+}
+
+/// Stroboscopic section at the driving phase 0 (mod 2 pi) of systems made
+/// autonomous with the phase as the last coordinate, like `flows::duffing`.
+pub fn stroboscopic_section() -> Section {
+    Section {
+        surface: |x| x[x.len() - 1].sin(),
+        crossing: Crossing::Upward,
+    }
+}
+
+/// Point where the step of length `dt` from `x` pierces the section, found by
+/// bisecting on the step length so that it is as accurate as the integrator.
+fn locate_crossing(f: VectorField, section: &Section, x: &DVector<f64>, dt: f64) -> DVector<f64> {
+    let g_start = (section.surface)(x);
+    let (mut low, mut high) = (0.0, dt);
+    for _ in 0..60 {
+        let h = 0.5 * (low + high);
+        let point = midpoint_step(f, x, h);
+        let g = (section.surface)(&point);
+        if g == 0.0 {
+            return point;
+        }
+        if g.signum() == g_start.signum() {
+            low = h;
+        } else {
+            high = h;
+        }
+    }
+    midpoint_step(f, x, 0.5 * (low + high))
+}
+
+/// Integrates the flow from `x0` and returns the first `n_points` crossings of
+/// the section after the transient time has passed.
+pub fn section_points(
+    f: VectorField,
+    section: &Section,
+    x0: &DVector<f64>,
+    dt: f64,
+    transient_time: f64,
+    n_points: usize,
+) -> Vec<DVector<f64>> {
+    let mut points = Vec::with_capacity(n_points);
+    let mut t = 0.0;
+    let mut x = x0.clone();
+    let mut g = (section.surface)(&x);
+    while points.len() < n_points {
+        let next_x = midpoint_step(f, &x, dt);
+        let next_g = (section.surface)(&next_x);
+        if t >= transient_time && section.is_crossed(g, next_g) {
+            points.push(locate_crossing(f, section, &x, dt));
+        }
+        t += dt;
+        x = next_x;
+        g = next_g;
+    }
+    points
+}
+
+/// First-return map (s_n, s_{n+1}) of the chosen coordinate of consecutive
+/// section points.
+pub fn first_return_map(points: &[DVector<f64>], coordinate: usize) -> Vec<(f64, f64)> {
+    points
+        .windows(2)
+        .map(|pair| (pair[0][coordinate], pair[1][coordinate]))
+        .collect()
+}
+
+pub fn save_section(points: &[DVector<f64>], file_path: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = Writer::from_path(file_path)?;
+    if let Some(first) = points.first() {
+        wtr.write_record((0..first.len()).map(|i| format!("x{}", i)))?;
+    }
+    for point in points {
+        wtr.write_record(point.iter().map(|v| v.to_string()))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn save_return_map(pairs: &[(f64, f64)], file_path: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = Writer::from_path(file_path)?;
+    wtr.write_record(["s_n", "s_n+1"])?;
+    for (s, s_next) in pairs {
+        wtr.write_record(&[s.to_string(), s_next.to_string()])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
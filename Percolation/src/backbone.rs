@@ -0,0 +1,191 @@
+/// Spanning cluster split into the part that would carry a current between
+/// the first and last rows and the parts that would not.
+pub struct Decomposition {
+    /// Sites on at least one self-avoiding path from the first to the last row.
+    pub backbone: Vec<Vec<bool>>,
+    /// Cluster sites outside the backbone.
+    pub dangling_ends: Vec<Vec<bool>>,
+    /// Bonds whose removal disconnects the first row from the last; all the
+    /// current passes through each of them.
+    pub red_bonds: Vec<((usize, usize), (usize, usize))>,
+}
+
+impl Decomposition {
+    pub fn backbone_mass(&self) -> usize {
+        self.backbone.iter().flatten().filter(|s| **s).count()
+    }
+
+    pub fn dangling_mass(&self) -> usize {
+        self.dangling_ends.iter().flatten().filter(|s| **s).count()
+    }
+
+    /// Site values for plotting: 0 empty, 1 dangling end, 2 backbone and 3
+    /// a site at the end of a red bond.
+    pub fn values(&self) -> Vec<Vec<u32>> {
+        let mut values = self
+            .backbone
+            .iter()
+            .zip(&self.dangling_ends)
+            .map(|(backbone, dangling)| {
+                backbone
+                    .iter()
+                    .zip(dangling)
+                    .map(|(b, d)| if *b { 2 } else { *d as u32 })
+                    .collect::<Vec<u32>>()
+            })
+            .collect::<Vec<Vec<u32>>>();
+        for &((i1, j1), (i2, j2)) in &self.red_bonds {
+            values[i1][j1] = 3;
+            values[i2][j2] = 3;
+        }
+        values
+    }
+}
+
+/// Undirected graph as adjacency lists of (neighbour, edge index).
+struct Graph {
+    adjacency: Vec<Vec<(usize, usize)>>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Graph {
+    fn new(nodes: usize) -> Self {
+        Graph {
+            adjacency: vec![vec![]; nodes],
+            edges: vec![],
+        }
+    }
+
+    fn add_edge(&mut self, a: usize, b: usize) -> usize {
+        let e = self.edges.len();
+        self.edges.push((a, b));
+        self.adjacency[a].push((b, e));
+        self.adjacency[b].push((a, e));
+        e
+    }
+
+    /// Hopcroft-Tarjan depth-first search in O(V + E), iterative so that
+    /// large clusters do not overflow the stack. Returns the biconnected
+    /// component of every edge reachable from `root` (`usize::MAX` for the
+    /// others) and whether each edge is a bridge.
+    fn biconnected_components(&self, root: usize) -> (Vec<usize>, Vec<bool>) {
+        let n = self.adjacency.len();
+        let mut discovery = vec![usize::MAX; n];
+        let mut low = vec![0; n];
+        let mut component = vec![usize::MAX; self.edges.len()];
+        let mut bridge = vec![false; self.edges.len()];
+        let mut components = 0;
+        let mut edge_stack = vec![];
+        // frames of (node, edge to the parent, next adjacency index)
+        let mut stack = vec![(root, usize::MAX, 0)];
+        discovery[root] = 0;
+        low[root] = 0;
+        let mut time = 1;
+        while let Some(&mut (u, parent_edge, ref mut next)) = stack.last_mut() {
+            if let Some(&(v, e)) = self.adjacency[u].get(*next) {
+                *next += 1;
+                if e == parent_edge {
+                    continue;
+                }
+                if discovery[v] == usize::MAX {
+                    discovery[v] = time;
+                    low[v] = time;
+                    time += 1;
+                    edge_stack.push(e);
+                    stack.push((v, e, 0));
+                } else if discovery[v] < discovery[u] {
+                    edge_stack.push(e);
+                    low[u] = low[u].min(discovery[v]);
+                }
+                continue;
+            }
+            stack.pop();
+            let Some(&(p, _, _)) = stack.last() else {
+                break;
+            };
+            low[p] = low[p].min(low[u]);
+            if low[u] >= discovery[p] {
+                while let Some(edge) = edge_stack.pop() {
+                    component[edge] = components;
+                    if edge == parent_edge {
+                        break;
+                    }
+                }
+                components += 1;
+            }
+            if low[u] > discovery[p] {
+                bridge[parent_edge] = true;
+            }
+        }
+        (component, bridge)
+    }
+}
+
+/// Splits a spanning cluster into backbone, dangling ends and red bonds.
+/// A source node joined to the cluster sites of the first row and a sink
+/// joined to those of the last row turn the question into graph theory:
+/// with an extra source-sink edge, the backbone is the biconnected
+/// component containing that edge, and without it the red bonds are the
+/// bridges inside the backbone.
+pub fn decompose(cluster: &[Vec<bool>]) -> Decomposition {
+    let (l, width) = (cluster.len(), cluster[0].len());
+    let mut index = vec![vec![usize::MAX; width]; l];
+    let mut sites = vec![];
+    for (i, row) in cluster.iter().enumerate() {
+        for (j, site) in row.iter().enumerate() {
+            if *site {
+                index[i][j] = sites.len();
+                sites.push((i, j));
+            }
+        }
+    }
+    let (source, sink) = (sites.len(), sites.len() + 1);
+    let mut graph = Graph::new(sites.len() + 2);
+    for &(i, j) in &sites {
+        if i + 1 < l && cluster[i + 1][j] {
+            graph.add_edge(index[i][j], index[i + 1][j]);
+        }
+        if j + 1 < width && cluster[i][j + 1] {
+            graph.add_edge(index[i][j], index[i][j + 1]);
+        }
+        if i == 0 {
+            graph.add_edge(source, index[i][j]);
+        }
+        if i == l - 1 {
+            graph.add_edge(index[i][j], sink);
+        }
+    }
+    let lattice_edges = graph.edges.len();
+
+    let (_, bridges) = graph.biconnected_components(source);
+    let closing_edge = graph.add_edge(source, sink);
+    let (components, _) = graph.biconnected_components(source);
+    let backbone_component = components[closing_edge];
+
+    let mut backbone = vec![vec![false; width]; l];
+    let mut red_bonds = vec![];
+    for (e, &(a, b)) in graph.edges.iter().enumerate().take(lattice_edges) {
+        if components[e] != backbone_component {
+            continue;
+        }
+        for node in [a, b] {
+            if node < sites.len() {
+                let (i, j) = sites[node];
+                backbone[i][j] = true;
+            }
+        }
+        if bridges[e] && a < sites.len() && b < sites.len() {
+            red_bonds.push((sites[a], sites[b]));
+        }
+    }
+    let dangling_ends = cluster
+        .iter()
+        .zip(&backbone)
+        .map(|(c, b)| c.iter().zip(b).map(|(c, b)| *c && !*b).collect())
+        .collect();
+    Decomposition {
+        backbone,
+        dangling_ends,
+        red_bonds,
+    }
+}
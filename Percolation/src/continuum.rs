@@ -0,0 +1,192 @@
+use rand::Rng;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Critical reduced densities eta_c = n * v of overlapping disks (2D) and
+/// spheres (3D) from the literature, with v the volume of one object. The
+/// covered fraction at the threshold is phi_c = 1 - exp(-eta_c).
+pub const DISK_ETA_C: f64 = 1.128087;
+pub const SPHERE_ETA_C: f64 = 0.341888;
+
+/// Volume of a D-dimensional ball; only 2D and 3D are supported.
+fn ball_volume<const D: usize>(radius: f64) -> f64 {
+    match D {
+        2 => PI * radius.powi(2),
+        3 => 4.0 / 3.0 * PI * radius.powi(3),
+        _ => panic!("only disks (D = 2) and spheres (D = 3) are supported"),
+    }
+}
+
+/// Covered fraction of space for a Poisson process of overlapping objects
+/// at reduced density eta.
+pub fn filling_fraction(eta: f64) -> f64 {
+    1.0 - (-eta).exp()
+}
+
+fn find(parent: &mut [usize], mut x: usize) -> usize {
+    while parent[x] != x {
+        parent[x] = parent[parent[x]];
+        x = parent[x];
+    }
+    x
+}
+
+/// Overlapping balls of equal radius in the box [0, size)^D with open
+/// boundaries. Clusters are kept up to date as balls are added, using a
+/// cell list of side 2 * radius, so that only balls in neighbouring cells
+/// can overlap, and a union-find over the balls.
+pub struct Continuum<const D: usize> {
+    pub size: f64,
+    pub radius: f64,
+    pub centres: Vec<[f64; D]>,
+    cells: HashMap<[i64; D], Vec<usize>>,
+    parent: Vec<usize>,
+    /// Whether the cluster of a root touches the faces x = 0 and x = size.
+    touches: Vec<(bool, bool)>,
+    spanning: bool,
+}
+
+impl<const D: usize> Continuum<D> {
+    pub fn empty(size: f64, radius: f64) -> Self {
+        Continuum {
+            size,
+            radius,
+            centres: vec![],
+            cells: HashMap::new(),
+            parent: vec![],
+            touches: vec![],
+            spanning: false,
+        }
+    }
+
+    /// Poisson process of balls at reduced density eta: the number of balls
+    /// is Poisson distributed with mean eta * size^D / v.
+    pub fn new<R: Rng>(size: f64, radius: f64, eta: f64, rng: &mut R) -> Self {
+        let mut continuum = Continuum::empty(size, radius);
+        let mean = eta * size.powi(D as i32) / ball_volume::<D>(radius);
+        // count arrivals of a unit-rate process up to time `mean`, with
+        // exponential waiting times -ln(u) for u in (0, 1]
+        let mut time = -(1.0 - rng.gen::<f64>()).ln();
+        while time < mean {
+            continuum.add_random(rng);
+            time -= (1.0 - rng.gen::<f64>()).ln();
+        }
+        continuum
+    }
+
+    /// Reduced density of the balls placed so far.
+    pub fn eta(&self) -> f64 {
+        self.centres.len() as f64 * ball_volume::<D>(self.radius) / self.size.powi(D as i32)
+    }
+
+    fn cell(&self, centre: &[f64; D]) -> [i64; D] {
+        centre.map(|x| (x / (2.0 * self.radius)).floor() as i64)
+    }
+
+    fn overlap(&self, a: usize, b: usize) -> bool {
+        let distance = self.centres[a]
+            .iter()
+            .zip(&self.centres[b])
+            .map(|(x, y)| (x - y).powi(2))
+            .sum::<f64>();
+        distance < (2.0 * self.radius).powi(2)
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (find(&mut self.parent, a), find(&mut self.parent, b));
+        if ra == rb {
+            return;
+        }
+        self.parent[ra] = rb;
+        self.touches[rb].0 |= self.touches[ra].0;
+        self.touches[rb].1 |= self.touches[ra].1;
+        self.spanning |= self.touches[rb].0 && self.touches[rb].1;
+    }
+
+    pub fn add(&mut self, centre: [f64; D]) {
+        let ball = self.centres.len();
+        self.centres.push(centre);
+        self.parent.push(ball);
+        let left = centre[0] < self.radius;
+        let right = centre[0] > self.size - self.radius;
+        self.touches.push((left, right));
+        self.spanning |= left && right;
+
+        let cell = self.cell(&centre);
+        let mut overlapping = vec![];
+        // the 3^D cells around and including the ball's own
+        for offset in 0..3_usize.pow(D as u32) {
+            let mut neighbour = cell;
+            let mut rest = offset;
+            for coordinate in neighbour.iter_mut() {
+                *coordinate += (rest % 3) as i64 - 1;
+                rest /= 3;
+            }
+            if let Some(others) = self.cells.get(&neighbour) {
+                overlapping.extend(others.iter().filter(|&&other| self.overlap(ball, other)));
+            }
+        }
+        for other in overlapping {
+            self.union(ball, other);
+        }
+        self.cells.entry(cell).or_default().push(ball);
+    }
+
+    pub fn add_random<R: Rng>(&mut self, rng: &mut R) {
+        let centre = [(); D].map(|_| rng.gen::<f64>() * self.size);
+        self.add(centre);
+    }
+
+    /// Whether a cluster connects the faces x = 0 and x = size.
+    pub fn spans(&self) -> bool {
+        self.spanning
+    }
+
+    /// Cluster label of every ball, numbered from 0.
+    pub fn labels(&mut self) -> Vec<usize> {
+        let mut numbers = HashMap::new();
+        (0..self.centres.len())
+            .map(|ball| {
+                let root = find(&mut self.parent, ball);
+                let next = numbers.len();
+                *numbers.entry(root).or_insert(next)
+            })
+            .collect()
+    }
+
+    /// Balls in the clusters that span the box.
+    pub fn spanning_balls(&mut self) -> Vec<bool> {
+        (0..self.centres.len())
+            .map(|ball| {
+                let root = find(&mut self.parent, ball);
+                self.touches[root].0 && self.touches[root].1
+            })
+            .collect()
+    }
+}
+
+/// Adds balls at random to an empty box until a cluster first spans it and
+/// returns the reduced density at that point, an estimate of eta_c that
+/// converges as the box grows.
+pub fn spanning_threshold<const D: usize, R: Rng>(size: f64, radius: f64, rng: &mut R) -> f64 {
+    let mut continuum = Continuum::<D>::empty(size, radius);
+    while !continuum.spans() {
+        continuum.add_random(rng);
+    }
+    continuum.eta()
+}
+
+/// Fraction of `runs` Poisson configurations at reduced density eta that
+/// contain a spanning cluster.
+pub fn spanning_probability<const D: usize, R: Rng>(
+    size: f64,
+    radius: f64,
+    eta: f64,
+    runs: usize,
+    rng: &mut R,
+) -> f64 {
+    let spanning = (0..runs)
+        .filter(|_| Continuum::<D>::new(size, radius, eta, rng).spans())
+        .count();
+    spanning as f64 / runs as f64
+}
@@ -0,0 +1,210 @@
+use rand::Rng;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+use crate::scaling::log_log_slope;
+
+/// Critical points of directed percolation from the literature.
+pub const BOND_PC_1D: f64 = 0.644700;
+pub const SITE_PC_1D: f64 = 0.705485;
+pub const BOND_PC_2D: f64 = 0.287338;
+pub const SITE_PC_2D: f64 = 0.344574;
+/// Critical infection rate of the 1D contact process.
+pub const CONTACT_LAMBDA_C: f64 = 3.29785;
+
+/// DP spreading exponents in 1+1 dimensions: P(t) ~ t^-delta,
+/// N(t) ~ t^theta and R^2(t) ~ t^(2/z).
+pub const DELTA_1D: f64 = 0.159464;
+pub const THETA_1D: f64 = 0.313686;
+pub const TWO_OVER_Z_1D: f64 = 1.265226;
+/// The same exponents in 2+1 dimensions.
+pub const DELTA_2D: f64 = 0.4505;
+pub const THETA_2D: f64 = 0.2295;
+pub const TWO_OVER_Z_2D: f64 = 1.1325;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DirectedModel {
+    /// Every bond to the next layer is open with probability p.
+    Bond,
+    /// Every site of the next layer is open with probability p and becomes
+    /// active if any of its parents is active.
+    Site,
+}
+
+/// Number of spatial dimensions; time is the extra, preferred direction.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Dimension {
+    /// 1+1 D on the tilted square lattice.
+    One,
+    /// 2+1 D on the body-centred cubic lattice.
+    Two,
+}
+
+impl Dimension {
+    fn children(self) -> &'static [(i64, i64)] {
+        match self {
+            Dimension::One => &[(-1, 0), (1, 0)],
+            Dimension::Two => &[(-1, -1), (-1, 1), (1, -1), (1, 1)],
+        }
+    }
+}
+
+/// Selects one observable of `SpreadingStatistics`, e.g. for plotting.
+pub type Observable = fn(&SpreadingStatistics) -> &Vec<f64>;
+
+/// Spreading from a single seed averaged over runs, at times 0..=t_max.
+pub struct SpreadingStatistics {
+    pub times: Vec<f64>,
+    /// Fraction of runs still active at time t.
+    pub survival: Vec<f64>,
+    /// Number of active sites at time t averaged over all runs.
+    pub active: Vec<f64>,
+    /// Mean square distance of active sites from the seed.
+    pub spreading: Vec<f64>,
+}
+
+impl SpreadingStatistics {
+    fn new(t_max: usize) -> Self {
+        SpreadingStatistics {
+            times: (0..=t_max).map(|t| t as f64).collect(),
+            survival: vec![0.0; t_max + 1],
+            active: vec![0.0; t_max + 1],
+            spreading: vec![0.0; t_max + 1],
+        }
+    }
+
+    fn record(&mut self, t: usize, active: &[(i64, i64)]) {
+        if active.is_empty() {
+            return;
+        }
+        self.survival[t] += 1.0;
+        self.active[t] += active.len() as f64;
+        self.spreading[t] += active
+            .iter()
+            .map(|(x, y)| (x * x + y * y) as f64)
+            .sum::<f64>();
+    }
+
+    fn normalise(mut self, runs: usize) -> Self {
+        for t in 0..self.times.len() {
+            if self.active[t] > 0.0 {
+                self.spreading[t] /= self.active[t];
+            }
+            self.survival[t] /= runs as f64;
+            self.active[t] /= runs as f64;
+        }
+        self
+    }
+
+    /// Exponents (delta, theta, 2/z) from log-log fits over t in
+    /// [t_max / 10, t_max], where the power laws hold at criticality.
+    pub fn exponents(&self) -> (f64, f64, f64) {
+        let start = self.times.len() / 10;
+        let fit = |values: &[f64]| {
+            let points = self.times[start.max(1)..]
+                .iter()
+                .zip(&values[start.max(1)..])
+                .filter(|(_, v)| **v > 0.0)
+                .map(|(t, v)| (*t, *v))
+                .collect::<Vec<(f64, f64)>>();
+            log_log_slope(&points)
+        };
+        (
+            -fit(&self.survival),
+            fit(&self.active),
+            fit(&self.spreading),
+        )
+    }
+}
+
+/// Directed percolation grown from a single active site at the origin on an
+/// unbounded lattice for `t_max` layers, averaged over `runs` clusters.
+pub fn directed_percolation<R: Rng>(
+    model: DirectedModel,
+    dimension: Dimension,
+    p: f64,
+    t_max: usize,
+    runs: usize,
+    rng: &mut R,
+) -> SpreadingStatistics {
+    let mut statistics = SpreadingStatistics::new(t_max);
+    for _ in 0..runs {
+        let mut active = vec![(0, 0)];
+        statistics.record(0, &active);
+        for t in 1..=t_max {
+            let mut next = HashSet::new();
+            match model {
+                DirectedModel::Bond => {
+                    for (x, y) in &active {
+                        for (dx, dy) in dimension.children() {
+                            if rng.gen::<f64>() < p {
+                                next.insert((x + dx, y + dy));
+                            }
+                        }
+                    }
+                }
+                DirectedModel::Site => {
+                    for (x, y) in &active {
+                        for (dx, dy) in dimension.children() {
+                            next.insert((x + dx, y + dy));
+                        }
+                    }
+                    next.retain(|_| rng.gen::<f64>() < p);
+                }
+            }
+            active = next.into_iter().collect();
+            if active.is_empty() {
+                break;
+            }
+            statistics.record(t, &active);
+        }
+    }
+    statistics.normalise(runs)
+}
+
+/// Contact process on the infinite 1D chain started from a single infected
+/// site: an infected site recovers with rate 1 and infects each neighbour
+/// with rate lambda / 2. Observables are sampled at integer times.
+pub fn contact_process<R: Rng>(
+    lambda: f64,
+    t_max: usize,
+    runs: usize,
+    rng: &mut R,
+) -> SpreadingStatistics {
+    let mut statistics = SpreadingStatistics::new(t_max);
+    for _ in 0..runs {
+        // active sites in a vector for random choice and a map from site to
+        // its index for constant-time removal
+        let mut active: Vec<(i64, i64)> = vec![(0, 0)];
+        let mut index = HashMap::from([((0, 0), 0)]);
+        let mut time = 0.0;
+        let mut next_sample = 0;
+        while !active.is_empty() && next_sample <= t_max {
+            // the waiting time to the next event is exponential with total
+            // rate (1 + lambda) per infected site, with u in (0, 1]
+            let u = 1.0 - rng.gen::<f64>();
+            time += -u.ln() / ((1.0 + lambda) * active.len() as f64);
+            // the current state holds at the sample times before the event
+            while next_sample <= t_max && (next_sample as f64) < time {
+                statistics.record(next_sample, &active);
+                next_sample += 1;
+            }
+            let chosen = rng.gen_range(0..active.len());
+            let (x, y) = active[chosen];
+            if rng.gen::<f64>() < lambda / (1.0 + lambda) {
+                let target = (x + if rng.gen::<bool>() { 1 } else { -1 }, y);
+                if let Entry::Vacant(entry) = index.entry(target) {
+                    entry.insert(active.len());
+                    active.push(target);
+                }
+            } else {
+                index.remove(&(x, y));
+                active.swap_remove(chosen);
+                if chosen < active.len() {
+                    index.insert(active[chosen], chosen);
+                }
+            }
+        }
+    }
+    statistics.normalise(runs)
+}
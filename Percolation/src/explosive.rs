@@ -0,0 +1,197 @@
+use rand::Rng;
+use std::collections::HashSet;
+
+/// How the edge to add is chosen among the candidates.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Rule {
+    /// The first candidate, i.e. ordinary random bond percolation.
+    Random,
+    /// The candidate with the smallest product of its endpoints' cluster sizes.
+    Product,
+    /// The candidate with the smallest sum of its endpoints' cluster sizes.
+    Sum,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Graph {
+    /// Bonds of an l x l square lattice with open boundaries.
+    Lattice(usize),
+    /// Erdos-Renyi process on n nodes: any pair of nodes can be joined.
+    Random(usize),
+}
+
+impl Graph {
+    pub fn nodes(self) -> usize {
+        match self {
+            Graph::Lattice(l) => l * l,
+            Graph::Random(n) => n,
+        }
+    }
+}
+
+/// Largest cluster after each added edge, as fractions of the nodes.
+pub struct ExplosiveResult {
+    /// Number of edges added divided by the number of nodes.
+    pub edges: Vec<f64>,
+    pub largest: Vec<f64>,
+}
+
+impl ExplosiveResult {
+    /// Largest increase of the largest cluster caused by one edge.
+    pub fn largest_jump(&self) -> f64 {
+        self.largest
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .fold(0.0, f64::max)
+    }
+
+    /// Edges per node needed to take the largest cluster from sqrt(N) nodes
+    /// to N / 2. It stays finite for a continuous transition but vanishes as
+    /// N grows for an abrupt one. None if the largest cluster never reaches
+    /// N / 2.
+    pub fn transition_width(&self, nodes: usize) -> Option<f64> {
+        let n = nodes as f64;
+        let first = |fraction: f64| {
+            self.largest
+                .iter()
+                .position(|s| *s >= fraction)
+                .map(|t| self.edges[t])
+        };
+        Some(first(0.5)? - first(n.sqrt() / n)?)
+    }
+}
+
+/// Union-find of clusters with their sizes.
+struct Clusters {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    largest: usize,
+}
+
+impl Clusters {
+    fn new(n: usize) -> Self {
+        Clusters {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            largest: 1,
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn cluster_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return;
+        }
+        let (small, large) = if self.size[ra] < self.size[rb] {
+            (ra, rb)
+        } else {
+            (rb, ra)
+        };
+        self.parent[small] = large;
+        self.size[large] += self.size[small];
+        self.largest = self.largest.max(self.size[large]);
+    }
+
+    fn weight(&mut self, rule: Rule, (a, b): (usize, usize)) -> usize {
+        let (sa, sb) = (self.cluster_size(a), self.cluster_size(b));
+        match rule {
+            Rule::Random => 0,
+            Rule::Product => sa * sb,
+            Rule::Sum => sa + sb,
+        }
+    }
+}
+
+fn lattice_bonds(l: usize) -> Vec<(usize, usize)> {
+    let mut bonds = vec![];
+    for i in 0..l {
+        for j in 0..l {
+            if i + 1 < l {
+                bonds.push((i * l + j, (i + 1) * l + j));
+            }
+            if j + 1 < l {
+                bonds.push((i * l + j, i * l + j + 1));
+            }
+        }
+    }
+    bonds
+}
+
+/// Achlioptas process: at every step `m` distinct candidate edges are drawn
+/// at random from those not yet added and the one preferred by `rule` is
+/// added, the others are put back. Runs until `max_edges` edges per node have
+/// been added, or the graph is full.
+pub fn achlioptas<R: Rng>(
+    graph: Graph,
+    rule: Rule,
+    m: usize,
+    max_edges: f64,
+    rng: &mut R,
+) -> ExplosiveResult {
+    let n = graph.nodes();
+    let mut clusters = Clusters::new(n);
+    let mut unused = match graph {
+        Graph::Lattice(l) => lattice_bonds(l),
+        Graph::Random(_) => vec![],
+    };
+    // edges of the random graph so far, as (smaller, larger) node pairs
+    let mut added = HashSet::new();
+    let steps = (max_edges * n as f64) as usize;
+    let mut result = ExplosiveResult {
+        edges: vec![0.0],
+        largest: vec![1.0 / n as f64],
+    };
+    for step in 1..=steps {
+        let edge = match graph {
+            Graph::Lattice(_) => {
+                if unused.is_empty() {
+                    break;
+                }
+                let best = rand::seq::index::sample(rng, unused.len(), m.min(unused.len()))
+                    .into_iter()
+                    .min_by_key(|&k| clusters.weight(rule, unused[k]))
+                    .unwrap();
+                unused.swap_remove(best)
+            }
+            Graph::Random(_) => {
+                let free = n * (n - 1) / 2 - added.len();
+                if free == 0 {
+                    break;
+                }
+                // resample self-loops and pairs already added or drawn; with
+                // at most a few n edges out of n^2 / 2 pairs this is quick
+                let mut candidates = vec![];
+                while candidates.len() < m.min(free) {
+                    let (a, b) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                    let edge = (a.min(b), a.max(b));
+                    if a != b && !added.contains(&edge) && !candidates.contains(&edge) {
+                        candidates.push(edge);
+                    }
+                }
+                let best = candidates
+                    .into_iter()
+                    .min_by_key(|&edge| clusters.weight(rule, edge))
+                    .unwrap();
+                added.insert(best);
+                best
+            }
+        };
+        clusters.union(edge.0, edge.1);
+        result.edges.push(step as f64 / n as f64);
+        result.largest.push(clusters.largest as f64 / n as f64);
+    }
+    result
+}
@@ -0,0 +1,144 @@
+use csv::Writer;
+use rand::Rng;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::rc::Rc;
+
+use crate::PercolationLattice;
+
+/// Tree density of the self-organised critical state for f/p -> 0.
+pub const CRITICAL_DENSITY: f64 = 0.408;
+
+/// Tree density, burnt trees and fires after every sweep of a run.
+pub struct ForestFireRun {
+    pub density: Vec<f64>,
+    pub burnt: Vec<usize>,
+    /// Size of every fire, in the order they happened.
+    pub fire_sizes: Vec<usize>,
+}
+
+impl ForestFireRun {
+    /// Writes the tree density and burnt trees of every sweep.
+    pub fn save_series(&self, file_path: &str) -> Result<(), Box<dyn Error>> {
+        let mut wtr = Writer::from_path(file_path)?;
+        wtr.write_record(["sweep", "density", "burnt"])?;
+        for (sweep, (density, burnt)) in self.density.iter().zip(&self.burnt).enumerate() {
+            wtr.write_record(&[sweep.to_string(), density.to_string(), burnt.to_string()])?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Writes the size of every fire, in the order they happened.
+    pub fn save_fire_sizes(&self, file_path: &str) -> Result<(), Box<dyn Error>> {
+        let mut wtr = Writer::from_path(file_path)?;
+        wtr.write_record(["size"])?;
+        for size in &self.fire_sizes {
+            wtr.write_record(&[size.to_string()])?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+/// Drossel-Schwabl forest-fire model on an l x l lattice, where an occupied
+/// site is a tree. At every update a random site is chosen: an empty one
+/// grows a tree with probability p and a tree is struck by lightning with
+/// probability f, which burns its whole cluster instantly.
+pub struct ForestFire {
+    lattice: PercolationLattice,
+    pub p: f64,
+    pub f: f64,
+    trees: usize,
+}
+
+impl ForestFire {
+    pub fn new(l: usize, p: f64, f: f64) -> Self {
+        ForestFire {
+            lattice: PercolationLattice::from_values(&vec![vec![0; l]; l], p as f32),
+            p,
+            f,
+            trees: 0,
+        }
+    }
+
+    pub fn density(&self) -> f64 {
+        self.trees as f64 / (self.lattice.l * self.lattice.l) as f64
+    }
+
+    pub fn values(&self) -> Vec<Vec<u32>> {
+        self.lattice
+            .sites
+            .iter()
+            .map(|row| row.iter().map(|site| site.borrow().value).collect())
+            .collect()
+    }
+
+    /// Burns the cluster of the tree at (i, j) with the burning method and
+    /// clears the burnt sites, which the burning method leaves labelled with
+    /// their step numbers. Returns the number of trees burnt.
+    fn burn(&mut self, i: usize, j: usize) -> usize {
+        let size = self.lattice.burn_from(&[(i, j)]).profile.iter().sum();
+        let site = &self.lattice.sites[i][j];
+        site.borrow_mut().value = 0;
+        let mut q = VecDeque::from([Rc::clone(site)]);
+        while let Some(site) = q.pop_front() {
+            for neighbour in site.borrow().neighbours.iter().flatten() {
+                if let Some(neighbour) = neighbour.upgrade() {
+                    if neighbour.borrow().value > 1 {
+                        neighbour.borrow_mut().value = 0;
+                        q.push_back(neighbour);
+                    }
+                }
+            }
+        }
+        self.trees -= size;
+        size
+    }
+
+    /// One update of a random site. Returns the size of the fire, if any.
+    pub fn step<R: Rng>(&mut self, rng: &mut R) -> Option<usize> {
+        let i = rng.gen_range(0..self.lattice.l);
+        let j = rng.gen_range(0..self.lattice.l);
+        let tree = self.lattice.sites[i][j].borrow().value == 1;
+        if !tree {
+            if rng.gen::<f64>() < self.p {
+                self.lattice.sites[i][j].borrow_mut().value = 1;
+                self.trees += 1;
+            }
+            None
+        } else if rng.gen::<f64>() < self.f {
+            Some(self.burn(i, j))
+        } else {
+            None
+        }
+    }
+
+    /// l^2 updates, so that every site is updated once on average. Returns
+    /// the sizes of the fires.
+    pub fn sweep<R: Rng>(&mut self, rng: &mut R) -> Vec<usize> {
+        (0..self.lattice.l * self.lattice.l)
+            .filter_map(|_| self.step(rng))
+            .collect()
+    }
+
+    /// Runs `transient` sweeps to reach the steady state and then records
+    /// `sweeps` more.
+    pub fn run<R: Rng>(&mut self, transient: usize, sweeps: usize, rng: &mut R) -> ForestFireRun {
+        for _ in 0..transient {
+            self.sweep(rng);
+        }
+        let mut run = ForestFireRun {
+            density: vec![],
+            burnt: vec![],
+            fire_sizes: vec![],
+        };
+        for _ in 0..sweeps {
+            let fires = self.sweep(rng);
+            run.burnt.push(fires.iter().sum());
+            run.fire_sizes.extend(fires);
+            run.density.push(self.density());
+        }
+        run
+    }
+}
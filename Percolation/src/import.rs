@@ -0,0 +1,185 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Characters of an ASCII map that mark an occupied site; '0', '.' and '-'
+/// mark empty ones.
+const OCCUPIED_CHARACTERS: &str = "1#Xx*@";
+const EMPTY_CHARACTERS: &str = "0.-";
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Grey level of every pixel in [0, 1], row by row from the top.
+pub type Brightness = Vec<Vec<f64>>;
+
+/// Decodes a PNG image of any colour type to grey levels, using the
+/// luminance of colour pixels and ignoring transparency.
+pub fn read_png(path: &Path) -> io::Result<Brightness> {
+    let mut decoder = png::Decoder::new(fs::File::open(path)?);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    let channels = info.color_type.samples();
+    Ok(buffer
+        .chunks(info.line_size)
+        .take(info.height as usize)
+        .map(|line| {
+            line.chunks(channels)
+                .take(info.width as usize)
+                .map(|pixel| {
+                    let grey = match pixel {
+                        [r, g, b, ..] if channels >= 3 => {
+                            0.299 * *r as f64 + 0.587 * *g as f64 + 0.114 * *b as f64
+                        }
+                        _ => pixel[0] as f64,
+                    };
+                    grey / 255.0
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// Decodes a plain (P2) or raw (P5) PGM image, with 8 or 16 bit samples.
+pub fn read_pgm(path: &Path) -> io::Result<Brightness> {
+    let bytes = fs::read(path)?;
+    // the header is whitespace separated fields, with comments from '#' to
+    // the end of the line
+    let mut position = 0;
+    let mut next_field = || {
+        loop {
+            match bytes.get(position) {
+                Some(b'#') => {
+                    while bytes.get(position).is_some_and(|b| *b != b'\n') {
+                        position += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => position += 1,
+                _ => break,
+            }
+        }
+        let start = position;
+        while bytes
+            .get(position)
+            .is_some_and(|b| !b.is_ascii_whitespace())
+        {
+            position += 1;
+        }
+        String::from_utf8_lossy(&bytes[start..position]).to_string()
+    };
+    let magic = next_field();
+    if magic != "P2" && magic != "P5" {
+        return Err(invalid_data(format!("not a PGM image: {}", magic)));
+    }
+    let mut number = |name: &str| {
+        let field = next_field();
+        field
+            .parse::<usize>()
+            .map_err(|_| invalid_data(format!("invalid PGM {}: {:?}", name, field)))
+    };
+    let width = number("width")?;
+    let height = number("height")?;
+    let max_value = number("maximum value")?;
+    if width == 0 || height == 0 {
+        return Err(invalid_data(format!(
+            "empty {} x {} PGM image",
+            width, height
+        )));
+    }
+    if max_value == 0 || max_value > 65535 {
+        return Err(invalid_data(format!(
+            "invalid PGM maximum value {}",
+            max_value
+        )));
+    }
+    let samples = if magic == "P2" {
+        (0..width * height)
+            .map(|_| number("sample"))
+            .collect::<io::Result<Vec<usize>>>()?
+    } else {
+        // a single whitespace character separates the header from the data
+        let data = &bytes[(position + 1).min(bytes.len())..];
+        let bytes_per_sample = if max_value < 256 { 1 } else { 2 };
+        if data.len() < width * height * bytes_per_sample {
+            return Err(invalid_data("PGM image data is truncated".to_string()));
+        }
+        data.chunks(bytes_per_sample)
+            .take(width * height)
+            .map(|sample| sample.iter().fold(0, |v, b| v * 256 + *b as usize))
+            .collect()
+    };
+    Ok(samples
+        .chunks(width)
+        .map(|row| row.iter().map(|s| *s as f64 / max_value as f64).collect())
+        .collect())
+}
+
+/// Site values of an ASCII map, one line per row, skipping blank lines.
+/// Every row must have the same length.
+pub fn parse_ascii(text: &str) -> io::Result<Vec<Vec<u32>>> {
+    let rows = text
+        .lines()
+        .map(|line| line.trim_end())
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| {
+            line.chars()
+                .map(|c| {
+                    if OCCUPIED_CHARACTERS.contains(c) {
+                        Ok(1)
+                    } else if EMPTY_CHARACTERS.contains(c) {
+                        Ok(0)
+                    } else {
+                        Err(invalid_data(format!(
+                            "unknown character {:?} on line {}",
+                            c,
+                            i + 1
+                        )))
+                    }
+                })
+                .collect::<io::Result<Vec<u32>>>()
+        })
+        .collect::<io::Result<Vec<Vec<u32>>>>()?;
+    if let Some(row) = rows.iter().find(|row| row.len() != rows[0].len()) {
+        return Err(invalid_data(format!(
+            "rows of {} and {} sites in the same map",
+            rows[0].len(),
+            row.len()
+        )));
+    }
+    Ok(rows)
+}
+
+/// Occupies the pixels brighter than `threshold`, or the darker ones with
+/// `dark_occupied`, e.g. for pores that appear dark in a micrograph.
+pub fn binarise(brightness: &Brightness, threshold: f64, dark_occupied: bool) -> Vec<Vec<u32>> {
+    brightness
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|b| ((*b > threshold) != dark_occupied) as u32)
+                .collect()
+        })
+        .collect()
+}
+
+/// Loads the site values of a lattice from a PNG or PGM image, thresholded
+/// as in `binarise`, or from an ASCII map for any other extension.
+pub fn load_lattice(path: &Path, threshold: f64, dark_occupied: bool) -> io::Result<Vec<Vec<u32>>> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let values = match extension.as_str() {
+        "png" => binarise(&read_png(path)?, threshold, dark_occupied),
+        "pgm" => binarise(&read_pgm(path)?, threshold, dark_occupied),
+        _ => parse_ascii(&fs::read_to_string(path)?)?,
+    };
+    if values.is_empty() || values[0].is_empty() {
+        return Err(invalid_data(format!("{} has no sites", path.display())));
+    }
+    Ok(values)
+}
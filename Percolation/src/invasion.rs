@@ -0,0 +1,148 @@
+use rand::Rng;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Site values of an invasion run, matching the lattice convention that
+/// 0 is an empty (defended) site and 1 an occupied (invaded) one.
+pub const DEFENDED: u32 = 0;
+pub const INVADED: u32 = 1;
+/// Defender fluid enclosed by the invader; it cannot be displaced.
+pub const TRAPPED: u32 = 2;
+
+const DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (0, -1), (1, 0), (0, 1)];
+
+pub struct InvasionResult {
+    /// Final value of every site: `DEFENDED`, `INVADED` or `TRAPPED`.
+    pub values: Vec<Vec<u32>>,
+    /// Strength of each invaded site, in invasion order.
+    pub accepted: Vec<f64>,
+    /// Number of invaded sites when the invader first reached the bottom row.
+    pub breakthrough_time: usize,
+}
+
+impl InvasionResult {
+    pub fn invaded(&self) -> Vec<Vec<bool>> {
+        self.values
+            .iter()
+            .map(|row| row.iter().map(|v| *v == INVADED).collect())
+            .collect()
+    }
+
+    /// Largest strength accepted before breakthrough, which approaches the
+    /// site percolation threshold p_c for large lattices.
+    pub fn threshold_estimate(&self) -> f64 {
+        self.accepted.iter().fold(0.0, |m, s| m.max(*s))
+    }
+}
+
+fn neighbours(l: usize, i: usize, j: usize) -> impl Iterator<Item = (usize, usize)> {
+    DIRECTIONS.iter().filter_map(move |&(di, dj)| {
+        let ni = i.checked_add_signed(di)?;
+        let nj = j.checked_add_signed(dj)?;
+        (ni < l && nj < l).then_some((ni, nj))
+    })
+}
+
+fn find(parent: &mut [usize], mut x: usize) -> usize {
+    while parent[x] != x {
+        parent[x] = parent[parent[x]];
+        x = parent[x];
+    }
+    x
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (ra, rb) = (find(parent, a), find(parent, b));
+    parent[ra] = rb;
+}
+
+/// Applies trapping to a run without it. Sites are added back in reverse
+/// invasion order to a union-find of the defender, with one extra node for
+/// the outlet (the bottom row); a site that is not connected to the outlet
+/// when it is added was already enclosed before it was invaded, so with an
+/// incompressible defender it would have been trapped instead.
+fn apply_trapping(values: &mut [Vec<u32>], invaded: &[(usize, usize)]) {
+    let l = values.len();
+    let outlet = l * l;
+    let mut parent = (0..=outlet).collect::<Vec<usize>>();
+    let mut present = vec![vec![false; l]; l];
+    let mut add = |i: usize, j: usize| {
+        present[i][j] = true;
+        if i == l - 1 {
+            union(&mut parent, i * l + j, outlet);
+        }
+        for (ni, nj) in neighbours(l, i, j) {
+            if present[ni][nj] {
+                union(&mut parent, i * l + j, ni * l + nj);
+            }
+        }
+        find(&mut parent, i * l + j) == find(&mut parent, outlet)
+    };
+    let defended = (0..l)
+        .flat_map(|i| (0..l).map(move |j| (i, j)))
+        .filter(|&(i, j)| values[i][j] == DEFENDED)
+        .collect::<Vec<(usize, usize)>>();
+    for &(i, j) in &defended {
+        add(i, j);
+    }
+    // regions enclosed by the invader at breakthrough
+    for &(i, j) in &defended {
+        if !add(i, j) {
+            values[i][j] = TRAPPED;
+        }
+    }
+    for &(i, j) in invaded.iter().rev() {
+        if !add(i, j) {
+            values[i][j] = TRAPPED;
+        }
+    }
+}
+
+/// Invasion percolation on an l x l lattice with uniformly random site
+/// strengths. The invader enters through the top row and at every step
+/// takes the weakest site on its perimeter, until it reaches the bottom
+/// row. With `trapping` the defender is incompressible, so regions cut off
+/// from the bottom row can no longer be invaded.
+pub fn invade<R: Rng>(l: usize, trapping: bool, rng: &mut R) -> InvasionResult {
+    let strengths = (0..l)
+        .map(|_| (0..l).map(|_| rng.gen::<f64>()).collect())
+        .collect::<Vec<Vec<f64>>>();
+    let mut values = vec![vec![DEFENDED; l]; l];
+    let mut on_perimeter = vec![vec![false; l]; l];
+    // strengths lie in [0, 1), where the bit pattern of an f64 is ordered
+    // like its value, so it can be used as an integer priority
+    let mut perimeter = BinaryHeap::new();
+    for j in 0..l {
+        on_perimeter[0][j] = true;
+        perimeter.push(Reverse((strengths[0][j].to_bits(), 0, j)));
+    }
+    let mut invaded = vec![];
+    while let Some(Reverse((_, i, j))) = perimeter.pop() {
+        values[i][j] = INVADED;
+        invaded.push((i, j));
+        if i == l - 1 {
+            break;
+        }
+        for (ni, nj) in neighbours(l, i, j) {
+            if values[ni][nj] == DEFENDED && !on_perimeter[ni][nj] {
+                on_perimeter[ni][nj] = true;
+                perimeter.push(Reverse((strengths[ni][nj].to_bits(), ni, nj)));
+            }
+        }
+    }
+    // trapping removes enclosed sites but does not change the order in which
+    // the others are invaded, so it can be applied afterwards
+    if trapping {
+        apply_trapping(&mut values, &invaded);
+    }
+    let accepted = invaded
+        .iter()
+        .filter(|&&(i, j)| values[i][j] == INVADED)
+        .map(|&(i, j)| strengths[i][j])
+        .collect::<Vec<f64>>();
+    InvasionResult {
+        values,
+        breakthrough_time: accepted.len(),
+        accepted,
+    }
+}
@@ -1,14 +1,27 @@
-use plotly::common::{Anchor, Font, Mode, Title};
-use plotly::layout::{self, Annotation, Axis, AxisType, Layout};
+use plotly::common::{Anchor, Font, Marker, Mode, Title};
+use plotly::layout::{Annotation, Axis, AxisType, Layout};
 use plotly::{HeatMap, ImageFormat, Plot, Scatter};
 use rand::Rng;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::collections::VecDeque;
-use std::io::Write;
-use std::path::Iter;
+use std::path::Path;
 use std::rc::{Rc, Weak};
 
+mod backbone;
+mod continuum;
+mod directed;
+mod explosive;
+mod forest_fire;
+mod fractal;
+mod import;
+mod invasion;
+mod resistor;
+mod scaling;
+
+/// Largest lattice whose site values are still written on the plot.
+const ANNOTATION_LIMIT: usize = 20;
+
 #[derive(Debug, Clone)]
 struct Site {
     value: u32,
@@ -27,16 +40,29 @@ impl Site {
                 0
             }
         };
-        return Site {
-            value: value,
+        Site {
+            value,
             neighbours: Vec::with_capacity(4),
-        };
+        }
     }
 }
 
+struct BurningResult {
+    /// Whether the fire reached the last row.
+    spanning: bool,
+    /// Length in steps of the shortest occupied path from the first to the
+    /// last row.
+    chemical_distance: Option<usize>,
+    /// Number of sites set on fire at each step.
+    profile: Vec<usize>,
+}
+
 struct PercolationLattice {
     sites: Vec<Vec<Rc<RefCell<Site>>>>,
+    /// Number of rows; spanning is from the first row to the last.
     l: usize,
+    /// Number of columns, which differs from `l` only for imported lattices.
+    width: usize,
     p: f32,
 }
 
@@ -51,16 +77,44 @@ impl PercolationLattice {
             sites.push(row);
         }
 
-        return PercolationLattice {
-            sites: sites,
-            l: l,
-            p: p,
+        PercolationLattice {
+            sites,
+            l,
+            width: l,
+            p,
+        }
+    }
+
+    /// Lattice with the given site values, e.g. the result of a growth model,
+    /// so that plotting and cluster analysis can be reused on it. All rows
+    /// must have the same length, which may differ from the number of rows.
+    fn from_values(values: &[Vec<u32>], p: f32) -> Self {
+        let sites = values
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|value| {
+                        Rc::new(RefCell::new(Site {
+                            value: *value,
+                            neighbours: Vec::with_capacity(4),
+                        }))
+                    })
+                    .collect()
+            })
+            .collect();
+        let lattice = PercolationLattice {
+            sites,
+            l: values.len(),
+            width: values.first().map_or(0, |row| row.len()),
+            p,
         };
+        lattice.initialize_neighbours();
+        lattice
     }
 
     fn initialize_neighbours(&self) {
         for i in 0..self.l {
-            for j in 0..self.l {
+            for j in 0..self.width {
                 if i > 0 {
                     self.sites[i][j]
                         .borrow_mut()
@@ -85,7 +139,7 @@ impl PercolationLattice {
                 } else {
                     self.sites[i][j].borrow_mut().neighbours.push(None);
                 }
-                if j < self.l - 1 {
+                if j < self.width - 1 {
                     self.sites[i][j]
                         .borrow_mut()
                         .neighbours
@@ -97,39 +151,57 @@ impl PercolationLattice {
         }
     }
 
-    fn burning_method(&self) -> bool {
-        let mut changed = true;
-        let mut n = 2;
-        // set the first row to 2
-        for j in 0..self.l {
-            if self.sites[0][j].borrow().value == 1 {
-                self.sites[0][j].borrow_mut().value = 2;
+    /// Sets fire to the occupied sites of the first row and spreads it to
+    /// occupied neighbours, one generation per step, labelling every burnt
+    /// site with its step number n + 2 (the first row gets 2). The fire is
+    /// spread with a queue, so each site is visited once.
+    fn burning_method(&self) -> BurningResult {
+        let first_row = (0..self.width)
+            .map(|j| (0, j))
+            .collect::<Vec<(usize, usize)>>();
+        self.burn_from(&first_row)
+    }
+
+    /// Burning method started from the occupied sites among `seeds` instead
+    /// of the first row.
+    fn burn_from(&self, seeds: &[(usize, usize)]) -> BurningResult {
+        let mut q = VecDeque::new();
+        for &(i, j) in seeds {
+            let site = &self.sites[i][j];
+            if site.borrow().value == 1 {
+                site.borrow_mut().value = 2;
+                q.push_back(Rc::clone(site));
             }
         }
 
-        while changed {
-            changed = false;
-            for i in 0..self.l {
-                for j in 0..self.l {
-                    if self.sites[i][j].borrow().value == n {
-                        for neighbour in &self.sites[i][j].borrow().neighbours {
-                            if let Some(neighbour) = neighbour {
-                                if let Some(neighbour) = neighbour.upgrade() {
-                                    if neighbour.borrow().value == 1 {
-                                        neighbour.borrow_mut().value = n + 1;
-                                        changed = true;
-                                    }
-                                }
-                            }
-                        }
+        let mut profile = vec![];
+        while let Some(site) = q.pop_front() {
+            let n = site.borrow().value;
+            let step = (n - 2) as usize;
+            if profile.len() <= step {
+                profile.push(0);
+            }
+            profile[step] += 1;
+            for neighbour in site.borrow().neighbours.iter().flatten() {
+                if let Some(neighbour) = neighbour.upgrade() {
+                    if neighbour.borrow().value == 1 {
+                        neighbour.borrow_mut().value = n + 1;
+                        q.push_back(neighbour);
                     }
                 }
             }
-            n += 1;
         }
-        return self.sites[self.l - 1]
+        let chemical_distance = self.sites[self.l - 1]
             .iter()
-            .any(|site| site.borrow().value > 1);
+            .map(|site| site.borrow().value)
+            .filter(|value| *value > 1)
+            .min()
+            .map(|value| (value - 2) as usize);
+        BurningResult {
+            spanning: chemical_distance.is_some(),
+            chemical_distance,
+            profile,
+        }
     }
 
     fn plot_lattice(&self, title: &str) {
@@ -139,10 +211,13 @@ impl PercolationLattice {
         let mut text_values = vec![];
 
         for i in 0..self.l {
-            for j in 0..self.l {
+            for j in 0..self.width {
                 x.push(j);
                 y.push(i);
                 z.push(self.sites[i][j].borrow().value);
+                if self.l.max(self.width) > ANNOTATION_LIMIT {
+                    continue;
+                }
                 text_values.push(
                     Annotation::new()
                         .text(format!("{}", self.sites[i][j].borrow().value))
@@ -167,7 +242,7 @@ impl PercolationLattice {
     }
 
     fn dfs(&self, i: usize, j: usize, visited: &mut Vec<Vec<bool>>) -> usize {
-        if i >= self.l || j >= self.l || visited[i][j] || self.sites[i][j].borrow().value == 0 {
+        if i >= self.l || j >= self.width || visited[i][j] || self.sites[i][j].borrow().value == 0 {
             return 0;
         }
         visited[i][j] = true;
@@ -176,7 +251,7 @@ impl PercolationLattice {
         for &(di, dj) in &directions {
             let ni = i.wrapping_add(di as usize);
             let nj = j.wrapping_add(dj as usize);
-            if ni < self.l && nj < self.l {
+            if ni < self.l && nj < self.width {
                 size += self.dfs(ni, nj, visited);
             }
         }
@@ -184,10 +259,10 @@ impl PercolationLattice {
     }
 
     fn max_cluster_size(&self) -> usize {
-        let mut visited = vec![vec![false; self.l]; self.l];
+        let mut visited = vec![vec![false; self.width]; self.l];
         let mut max_size = 0;
         for i in 0..self.l {
-            for j in 0..self.l {
+            for j in 0..self.width {
                 if !visited[i][j] && self.sites[i][j].borrow().value >= 1 {
                     let size = self.dfs(i, j, &mut visited);
                     if size > max_size {
@@ -199,6 +274,50 @@ impl PercolationLattice {
         max_size
     }
 
+    /// Sites of the largest cluster connecting the top and bottom rows, or
+    /// `None` if the lattice does not percolate.
+    fn spanning_cluster(&self) -> Option<Vec<Vec<bool>>> {
+        let mut labels = vec![vec![0; self.width]; self.l];
+        let mut sizes = vec![0];
+        for i in 0..self.l {
+            for j in 0..self.width {
+                if labels[i][j] != 0 || self.sites[i][j].borrow().value == 0 {
+                    continue;
+                }
+                let label = sizes.len();
+                sizes.push(0);
+                labels[i][j] = label;
+                let mut q = VecDeque::from([(i, j)]);
+                while let Some((ci, cj)) = q.pop_front() {
+                    sizes[label] += 1;
+                    let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+                    for (di, dj) in directions {
+                        let ni = ci.wrapping_add_signed(di);
+                        let nj = cj.wrapping_add_signed(dj);
+                        if ni < self.l
+                            && nj < self.width
+                            && labels[ni][nj] == 0
+                            && self.sites[ni][nj].borrow().value != 0
+                        {
+                            labels[ni][nj] = label;
+                            q.push_back((ni, nj));
+                        }
+                    }
+                }
+            }
+        }
+        let spanning = labels[0]
+            .iter()
+            .filter(|label| **label != 0 && labels[self.l - 1].contains(label))
+            .max_by_key(|label| sizes[**label])?;
+        Some(
+            labels
+                .iter()
+                .map(|row| row.iter().map(|label| label == spanning).collect())
+                .collect(),
+        )
+    }
+
     fn hoshen_kopelman(&self, plot: bool) -> Vec<usize> {
         let n = PercolationLattice {
             sites: self
@@ -216,23 +335,22 @@ impl PercolationLattice {
                 })
                 .collect(),
             l: self.l,
+            width: self.width,
             p: self.p,
         };
         n.initialize_neighbours();
         let mut k = 2;
         let mut m = vec![];
         for i in 0..n.l {
-            for j in 0..n.l {
+            for j in 0..n.width {
                 if n.sites[i][j].borrow().value == 1 {
                     let mut size = 1;
                     n.sites[i][j].borrow_mut().value = k;
                     let mut q: VecDeque<Rc<RefCell<Site>>> = VecDeque::new();
-                    for neighbour in &n.sites[i][j].borrow().neighbours {
-                        if let Some(neighbour) = neighbour {
-                            if let Some(neighbour) = neighbour.upgrade() {
-                                if neighbour.borrow().value == 1 {
-                                    q.push_back(neighbour);
-                                }
+                    for neighbour in n.sites[i][j].borrow().neighbours.iter().flatten() {
+                        if let Some(neighbour) = neighbour.upgrade() {
+                            if neighbour.borrow().value == 1 {
+                                q.push_back(neighbour);
                             }
                         }
                     }
@@ -242,12 +360,10 @@ impl PercolationLattice {
                         if site.borrow().value == 1 {
                             size += 1;
                             site.borrow_mut().value = k;
-                            for neighbour in &site.borrow().neighbours {
-                                if let Some(neighbour) = neighbour {
-                                    if let Some(neighbour) = neighbour.upgrade() {
-                                        if neighbour.borrow().value == 1 {
-                                            q.push_back(neighbour);
-                                        }
+                            for neighbour in site.borrow().neighbours.iter().flatten() {
+                                if let Some(neighbour) = neighbour.upgrade() {
+                                    if neighbour.borrow().value == 1 {
+                                        q.push_back(neighbour);
                                     }
                                 }
                             }
@@ -261,7 +377,7 @@ impl PercolationLattice {
         if plot {
             n.plot_lattice(format!("Hoshen-Kopelman Clusters for p={}", self.p).as_str());
         }
-        return m;
+        m
     }
 }
 
@@ -284,13 +400,13 @@ fn monte_carlo(t: i32, l: usize, p: f32) -> (f32, f32) {
         let pl = PercolationLattice::new(l, p);
         pl.initialize_neighbours();
         s_maxes.push(pl.max_cluster_size());
-        if pl.burning_method() {
+        if pl.burning_method().spanning {
             percolations += 1;
         }
     }
     let percolation_probability = percolations as f32 / t as f32;
     let s_max_avg = s_maxes.iter().sum::<usize>() as f32 / t as f32;
-    return (percolation_probability, s_max_avg);
+    (percolation_probability, s_max_avg)
 }
 
 fn monte_carlo_examples() {
@@ -382,8 +498,1001 @@ fn occupation_probability_examples() {
     }
 }
 
+fn invasion_percolation_examples() {
+    let mut rng = rand::thread_rng();
+    for trapping in [false, true] {
+        let mode = if trapping { "with" } else { "without" };
+        let result = invasion::invade(100, trapping, &mut rng);
+        let lattice = PercolationLattice::from_values(&result.values, 0.0);
+        lattice.plot_lattice(format!("Invasion percolation {} trapping", mode).as_str());
+    }
+
+    // mass-radius scaling of the invaded cluster at breakthrough
+    let l = 256;
+    let t = 20;
+    let radii = scaling::doubling_radii(4, l / 4);
+    let mut plot = Plot::new();
+    for trapping in [false, true] {
+        let mode = if trapping { "with" } else { "without" };
+        let curves = (0..t)
+            .map(|_| {
+                let result = invasion::invade(l, trapping, &mut rng);
+                scaling::mass_radius(&result.invaded(), &radii, 200, &mut rng)
+            })
+            .collect::<Vec<Vec<(f64, f64)>>>();
+        let points = scaling::mean_curve(&curves);
+        let d = scaling::log_log_slope(&points);
+        println!("Invasion {} trapping: mass-radius D = {:.3}", mode, d);
+        let (r, m): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+        let trace = Scatter::new(r, m)
+            .mode(Mode::LinesMarkers)
+            .name(format!("{} trapping, D = {:.3}", mode, d).as_str());
+        plot.add_trace(trace);
+    }
+    let layout = Layout::new()
+        .title("Invasion Percolation Mass-Radius Scaling")
+        .x_axis(Axis::new().title("r").type_(AxisType::Log))
+        .y_axis(Axis::new().title("M(r)").type_(AxisType::Log));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+
+    // breakthrough time grows with the lattice size as L^D
+    let l_vec = vec![16, 32, 64, 128];
+    let mut plot = Plot::new();
+    for trapping in [false, true] {
+        let mode = if trapping { "with" } else { "without" };
+        let mut points = vec![];
+        for &l in &l_vec {
+            let mut time = 0.0;
+            let mut threshold = 0.0;
+            for _ in 0..t {
+                let result = invasion::invade(l, trapping, &mut rng);
+                time += result.breakthrough_time as f64 / t as f64;
+                threshold += result.threshold_estimate() / t as f64;
+            }
+            println!(
+                "Invasion {} trapping, l = {}: breakthrough time = {:.1}, max accepted strength = {:.4}",
+                mode, l, time, threshold
+            );
+            points.push((l as f64, time));
+        }
+        let d = scaling::log_log_slope(&points);
+        let (x, y): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+        let trace = Scatter::new(x, y)
+            .mode(Mode::LinesMarkers)
+            .name(format!("{} trapping, slope = {:.3}", mode, d).as_str());
+        plot.add_trace(trace);
+    }
+    let layout = Layout::new()
+        .title("Breakthrough Time vs L")
+        .x_axis(Axis::new().title("L").type_(AxisType::Log))
+        .y_axis(Axis::new().title("breakthrough time").type_(AxisType::Log));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+}
+
+fn directed_percolation_examples() {
+    use directed::{Dimension, DirectedModel, Observable, SpreadingStatistics};
+    let mut rng = rand::thread_rng();
+    let t_max = 1000;
+    let runs = 10000;
+    let cases: Vec<(&str, SpreadingStatistics)> = vec![
+        (
+            "bond DP 1+1",
+            directed::directed_percolation(
+                DirectedModel::Bond,
+                Dimension::One,
+                directed::BOND_PC_1D,
+                t_max,
+                runs,
+                &mut rng,
+            ),
+        ),
+        (
+            "site DP 1+1",
+            directed::directed_percolation(
+                DirectedModel::Site,
+                Dimension::One,
+                directed::SITE_PC_1D,
+                t_max,
+                runs,
+                &mut rng,
+            ),
+        ),
+        (
+            "bond DP 2+1",
+            directed::directed_percolation(
+                DirectedModel::Bond,
+                Dimension::Two,
+                directed::BOND_PC_2D,
+                t_max / 4,
+                runs,
+                &mut rng,
+            ),
+        ),
+        (
+            "site DP 2+1",
+            directed::directed_percolation(
+                DirectedModel::Site,
+                Dimension::Two,
+                directed::SITE_PC_2D,
+                t_max / 4,
+                runs,
+                &mut rng,
+            ),
+        ),
+        (
+            "contact process 1D",
+            directed::contact_process(directed::CONTACT_LAMBDA_C, t_max, runs, &mut rng),
+        ),
+    ];
+    println!(
+        "DP 1+1 literature: delta = {:.4}, theta = {:.4}, 2/z = {:.4}",
+        directed::DELTA_1D,
+        directed::THETA_1D,
+        directed::TWO_OVER_Z_1D
+    );
+    println!(
+        "DP 2+1 literature: delta = {:.4}, theta = {:.4}, 2/z = {:.4}",
+        directed::DELTA_2D,
+        directed::THETA_2D,
+        directed::TWO_OVER_Z_2D
+    );
+    let observables: [(&str, &str, Observable); 3] = [
+        ("Survival Probability", "P(t)", |s| &s.survival),
+        ("Mean Number of Active Sites", "N(t)", |s| &s.active),
+        ("Mean Square Spreading", "R^2(t)", |s| &s.spreading),
+    ];
+    for (title, y_title, observable) in observables {
+        let mut plot = Plot::new();
+        for (name, statistics) in &cases {
+            let trace = Scatter::new(statistics.times.clone(), observable(statistics).clone())
+                .mode(Mode::Lines)
+                .name(name);
+            plot.add_trace(trace);
+        }
+        let layout = Layout::new()
+            .title(format!("{} at Criticality", title).as_str())
+            .x_axis(Axis::new().title("t").type_(AxisType::Log))
+            .y_axis(Axis::new().title(y_title).type_(AxisType::Log));
+        plot.set_layout(layout);
+        plot.show_image(ImageFormat::PNG, 1000, 800);
+    }
+    for (name, statistics) in &cases {
+        let (delta, theta, two_over_z) = statistics.exponents();
+        println!(
+            "{}: delta = {:.4}, theta = {:.4}, 2/z = {:.4}",
+            name, delta, theta, two_over_z
+        );
+    }
+}
+
+fn fractal_dimension_examples() {
+    use fractal::Connectivity;
+    let mut rng = rand::thread_rng();
+    let pc = 0.592746;
+
+    // spanning cluster with its hull and accessible perimeter
+    let l = 128;
+    let cluster = loop {
+        if let Some(cluster) = PercolationLattice::new(l, pc).spanning_cluster() {
+            break cluster;
+        }
+    };
+    let hull = fractal::external_perimeter(&cluster, Connectivity::Eight);
+    let accessible = fractal::external_perimeter(&cluster, Connectivity::Four);
+    let values = (0..l)
+        .map(|i| {
+            (0..l)
+                .map(|j| {
+                    if cluster[i][j] {
+                        1
+                    } else if accessible[i][j] {
+                        3
+                    } else if hull[i][j] {
+                        2
+                    } else {
+                        0
+                    }
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<u32>>>();
+    PercolationLattice::from_values(&values, pc)
+        .plot_lattice("Spanning cluster (1), hull (2) and accessible perimeter (3) at p_c");
+
+    // dimensions averaged over spanning realisations at p_c
+    let l = 256;
+    let t = 50;
+    let radii = scaling::doubling_radii(4, l / 4);
+    let sizes = scaling::doubling_radii(4, l / 8);
+    let mut mass_radius = vec![];
+    let mut boxes = [
+        vec![0.0; sizes.len()],
+        vec![0.0; sizes.len()],
+        vec![0.0; sizes.len()],
+    ];
+    let mut spanning = 0;
+    while spanning < t {
+        let Some(cluster) = PercolationLattice::new(l, pc).spanning_cluster() else {
+            continue;
+        };
+        spanning += 1;
+        mass_radius.push(scaling::mass_radius(&cluster, &radii, 200, &mut rng));
+        let sets = [
+            cluster.clone(),
+            fractal::external_perimeter(&cluster, Connectivity::Eight),
+            fractal::external_perimeter(&cluster, Connectivity::Four),
+        ];
+        for (counts, set) in boxes.iter_mut().zip(&sets) {
+            for (count, (_, n)) in counts.iter_mut().zip(fractal::box_counting(set, &sizes)) {
+                *count += n / t as f64;
+            }
+        }
+    }
+    let mass_radius = scaling::mean_curve(&mass_radius);
+    println!(
+        "Spanning cluster: mass-radius D = {:.3} (theory 91/48 = {:.3})",
+        scaling::log_log_slope(&mass_radius),
+        91.0 / 48.0
+    );
+
+    let mut plot = Plot::new();
+    let names = ["cluster", "hull", "accessible perimeter"];
+    let theory = [91.0 / 48.0, 7.0 / 4.0, 4.0 / 3.0];
+    for ((name, counts), d_theory) in names.iter().zip(boxes).zip(theory) {
+        let points = sizes
+            .iter()
+            .map(|s| *s as f64)
+            .zip(counts)
+            .collect::<Vec<(f64, f64)>>();
+        let d = fractal::box_dimension(&points);
+        println!(
+            "Box-counting dimension of the {}: {:.3} (theory {:.3})",
+            name, d, d_theory
+        );
+        let (x, y): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+        let trace = Scatter::new(x, y)
+            .mode(Mode::LinesMarkers)
+            .name(format!("{}, D = {:.3}", name, d).as_str());
+        plot.add_trace(trace);
+    }
+    let layout = Layout::new()
+        .title("Box Counting at p_c")
+        .x_axis(Axis::new().title("box size").type_(AxisType::Log))
+        .y_axis(Axis::new().title("N(box size)").type_(AxisType::Log));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+
+    // finite-size scaling of the spanning cluster mass, M(L) ~ L^D
+    let l_vec = vec![16, 32, 64, 128, 256];
+    let mut points = vec![];
+    for &l in &l_vec {
+        let mut mass = 0.0;
+        let mut spanning = 0;
+        while spanning < t {
+            let Some(cluster) = PercolationLattice::new(l, pc).spanning_cluster() else {
+                continue;
+            };
+            spanning += 1;
+            mass += cluster.iter().flatten().filter(|s| **s).count() as f64 / t as f64;
+        }
+        points.push((l as f64, mass));
+    }
+    let d = scaling::log_log_slope(&points);
+    println!("Spanning cluster mass M(L) ~ L^D: D = {:.3}", d);
+    let (x, y): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+    let mut plot = Plot::new();
+    plot.add_trace(
+        Scatter::new(x, y)
+            .mode(Mode::LinesMarkers)
+            .name(format!("D = {:.3}", d).as_str()),
+    );
+    let (r, m): (Vec<f64>, Vec<f64>) = mass_radius.into_iter().unzip();
+    plot.add_trace(Scatter::new(r, m).mode(Mode::LinesMarkers).name("M(r)"));
+    let layout = Layout::new()
+        .title("Spanning Cluster Mass vs L and Mass-Radius M(r)")
+        .x_axis(Axis::new().title("L, r").type_(AxisType::Log))
+        .y_axis(Axis::new().title("M").type_(AxisType::Log));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+}
+
+fn chemical_distance_examples() {
+    let pc = 0.592746;
+    let t = 200;
+
+    // shortest-path dimension from the chemical distance across the lattice
+    let l_vec = vec![16, 32, 64, 128, 256];
+    let mut points = vec![];
+    let mut profiles = vec![];
+    for &l in &l_vec {
+        let mut distance = 0.0;
+        let mut profile: Vec<f64> = vec![];
+        let mut spanning = 0;
+        while spanning < t {
+            let pl = PercolationLattice::new(l, pc);
+            pl.initialize_neighbours();
+            let result = pl.burning_method();
+            let Some(chemical_distance) = result.chemical_distance else {
+                continue;
+            };
+            spanning += 1;
+            distance += chemical_distance as f64 / t as f64;
+            if profile.len() < result.profile.len() {
+                profile.resize(result.profile.len(), 0.0);
+            }
+            for (p, burnt) in profile.iter_mut().zip(result.profile) {
+                *p += burnt as f64 / t as f64;
+            }
+        }
+        println!("l = {}: mean chemical distance = {:.1}", l, distance);
+        points.push((l as f64, distance));
+        profiles.push((l, profile));
+    }
+    let d_min = scaling::log_log_slope(&points);
+    println!(
+        "Shortest-path dimension d_min = {:.4} (literature 1.1307)",
+        d_min
+    );
+    let (x, y): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+    let mut plot = Plot::new();
+    plot.add_trace(
+        Scatter::new(x, y)
+            .mode(Mode::LinesMarkers)
+            .name(format!("d_min = {:.4}", d_min).as_str()),
+    );
+    let layout = Layout::new()
+        .title("Chemical Distance vs L at p_c")
+        .x_axis(Axis::new().title("L").type_(AxisType::Log))
+        .y_axis(Axis::new().title("chemical distance").type_(AxisType::Log));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+
+    // burning-time profile: sites set on fire at each step
+    let mut plot = Plot::new();
+    for (l, profile) in profiles {
+        let steps = (0..profile.len()).map(|n| n as f64).collect::<Vec<f64>>();
+        let trace = Scatter::new(steps, profile)
+            .mode(Mode::Lines)
+            .name(format!("l = {}", l).as_str());
+        plot.add_trace(trace);
+    }
+    let layout = Layout::new()
+        .title("Burning-Time Profile at p_c")
+        .x_axis(Axis::new().title("step n"))
+        .y_axis(Axis::new().title("sites burnt at step n"));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+}
+
+fn backbone_examples() {
+    let pc = 0.592746;
+    let t = 100;
+
+    let cluster = loop {
+        if let Some(cluster) = PercolationLattice::new(64, pc).spanning_cluster() {
+            break cluster;
+        }
+    };
+    let decomposition = backbone::decompose(&cluster);
+    PercolationLattice::from_values(&decomposition.values(), pc)
+        .plot_lattice("Dangling ends (1), backbone (2) and red bonds (3) at p_c");
+
+    let l_vec = vec![16, 32, 64, 128, 256];
+    let mut backbone_points = vec![];
+    let mut dangling_points = vec![];
+    let mut red_points = vec![];
+    for &l in &l_vec {
+        let (mut backbone_mass, mut dangling_mass, mut red_bonds) = (0.0, 0.0, 0.0);
+        let mut spanning = 0;
+        while spanning < t {
+            let Some(cluster) = PercolationLattice::new(l, pc).spanning_cluster() else {
+                continue;
+            };
+            spanning += 1;
+            let decomposition = backbone::decompose(&cluster);
+            backbone_mass += decomposition.backbone_mass() as f64 / t as f64;
+            dangling_mass += decomposition.dangling_mass() as f64 / t as f64;
+            red_bonds += decomposition.red_bonds.len() as f64 / t as f64;
+        }
+        println!(
+            "l = {}: backbone = {:.1}, dangling ends = {:.1}, red bonds = {:.2}",
+            l, backbone_mass, dangling_mass, red_bonds
+        );
+        backbone_points.push((l as f64, backbone_mass));
+        dangling_points.push((l as f64, dangling_mass));
+        red_points.push((l as f64, red_bonds));
+    }
+    let mut plot = Plot::new();
+    let series = [
+        ("backbone", backbone_points, "1.6432"),
+        ("dangling ends", dangling_points, "91/48"),
+        ("red bonds", red_points, "3/4"),
+    ];
+    for (name, points, theory) in series {
+        let exponent = scaling::log_log_slope(&points);
+        println!("{} ~ L^{:.4} (theory {})", name, exponent, theory);
+        let (x, y): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+        let trace = Scatter::new(x, y)
+            .mode(Mode::LinesMarkers)
+            .name(format!("{}, exponent = {:.3}", name, exponent).as_str());
+        plot.add_trace(trace);
+    }
+    let layout = Layout::new()
+        .title("Spanning Cluster Decomposition at p_c")
+        .x_axis(Axis::new().title("L").type_(AxisType::Log))
+        .y_axis(
+            Axis::new()
+                .title("mass, number of red bonds")
+                .type_(AxisType::Log),
+        );
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+}
+
+fn resistor_network_examples() {
+    let pc = 0.592746;
+    let t = 50;
+
+    // current through the sites of one spanning cluster
+    let l = 64;
+    let cluster = loop {
+        if let Some(cluster) = PercolationLattice::new(l, pc).spanning_cluster() {
+            break cluster;
+        }
+    };
+    let network = resistor::solve(&cluster);
+    println!(
+        "l = {}: conductance = {:.5} after {} conjugate gradient iterations",
+        l, network.conductance, network.iterations
+    );
+    let site_currents = network.site_currents();
+    let mut x = vec![];
+    let mut y = vec![];
+    let mut z = vec![];
+    for (i, row) in site_currents.iter().enumerate() {
+        for (j, current) in row.iter().enumerate() {
+            x.push(j);
+            y.push(i);
+            z.push((current / network.conductance).max(1e-12).log10());
+        }
+    }
+    let mut plot = Plot::new();
+    plot.add_trace(HeatMap::new(x, y, z));
+    plot.set_layout(
+        Layout::new().title(Title::from(
+            format!(
+                "log10 of site current / total current, G = {:.4}",
+                network.conductance
+            )
+            .as_str(),
+        )),
+    );
+    plot.show_image(ImageFormat::PNG, 600, 600);
+
+    // conductance at p_c falls as L^(-t/nu), with nu = 4/3
+    let l_vec = vec![16, 32, 64, 128];
+    let mut points = vec![];
+    let mut largest_currents = vec![];
+    for &l in &l_vec {
+        let mut conductance = 0.0;
+        let mut spanning = 0;
+        while spanning < t {
+            let Some(cluster) = PercolationLattice::new(l, pc).spanning_cluster() else {
+                continue;
+            };
+            spanning += 1;
+            let network = resistor::solve(&cluster);
+            conductance += network.conductance / t as f64;
+            if l == *l_vec.last().unwrap() {
+                largest_currents.extend(network.currents.iter().map(|c| c / network.conductance));
+            }
+        }
+        println!("l = {}: conductance at p_c = {:.5}", l, conductance);
+        points.push((l as f64, conductance));
+    }
+    let t_over_nu = -scaling::log_log_slope(&points);
+    println!(
+        "Conductivity exponent t = {:.3} (literature 1.310)",
+        t_over_nu * 4.0 / 3.0
+    );
+    let (x, y): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+    let mut plot = Plot::new();
+    plot.add_trace(
+        Scatter::new(x, y)
+            .mode(Mode::LinesMarkers)
+            .name(format!("t/nu = {:.3}", t_over_nu).as_str()),
+    );
+    let layout = Layout::new()
+        .title("Conductance vs L at p_c")
+        .x_axis(Axis::new().title("L").type_(AxisType::Log))
+        .y_axis(Axis::new().title("G").type_(AxisType::Log));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+
+    // distribution of bond currents, normalised by the total current
+    let mut histogram = HashMap::new();
+    let currents = largest_currents.iter().filter(|c| **c > 1e-12);
+    let total = currents.clone().count();
+    for current in currents {
+        let bin = (current.log10() * 5.0).floor() as i64;
+        *histogram.entry(bin).or_insert(0) += 1;
+    }
+    let mut bins = histogram.into_iter().collect::<Vec<(i64, usize)>>();
+    bins.sort();
+    let (x, y): (Vec<f64>, Vec<f64>) = bins
+        .into_iter()
+        .map(|(bin, count)| ((bin as f64 + 0.5) / 5.0, (count as f64 / total as f64).ln()))
+        .unzip();
+    let mut plot = Plot::new();
+    plot.add_trace(Scatter::new(x, y).mode(Mode::LinesMarkers).name("bonds"));
+    let layout = Layout::new()
+        .title(format!("Current Distribution at p_c, L = {}", l_vec.last().unwrap()).as_str())
+        .x_axis(Axis::new().title("log10(i / I)"))
+        .y_axis(Axis::new().title("ln(fraction of bonds)"));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+
+    // conductivity above the threshold
+    let l = 64;
+    let p_vals = (60..=90).step_by(2).map(|x| x as f64 / 100.0);
+    let mut conductivities = vec![];
+    for p in p_vals.clone() {
+        let mut conductance = 0.0;
+        for _ in 0..t {
+            if let Some(cluster) = PercolationLattice::new(l, p as f32).spanning_cluster() {
+                conductance += resistor::solve(&cluster).conductance / t as f64;
+            }
+        }
+        conductivities.push(conductance);
+    }
+    let mut plot = Plot::new();
+    plot.add_trace(
+        Scatter::new(p_vals.collect(), conductivities)
+            .mode(Mode::LinesMarkers)
+            .name(format!("l = {}", l).as_str()),
+    );
+    let layout = Layout::new()
+        .title("Conductivity vs p")
+        .x_axis(Axis::new().title("p"))
+        .y_axis(Axis::new().title("sigma(p)"));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+}
+
+fn continuum_percolation_examples() {
+    use continuum::{Continuum, DISK_ETA_C, SPHERE_ETA_C};
+    let mut rng = rand::thread_rng();
+    let runs = 200;
+
+    // one configuration of disks at the threshold, spanning clusters in red
+    let size = 30.0;
+    let mut disks = Continuum::<2>::new(size, 1.0, DISK_ETA_C, &mut rng);
+    let clusters = disks
+        .labels()
+        .into_iter()
+        .max()
+        .map_or(0, |label| label + 1);
+    println!(
+        "{} disks at eta_c form {} clusters, spanning: {}",
+        disks.centres.len(),
+        clusters,
+        disks.spans()
+    );
+    let spanning = disks.spanning_balls();
+    let mut plot = Plot::new();
+    for (name, colour, in_spanning) in [("other", "grey", false), ("spanning", "red", true)] {
+        let (x, y): (Vec<f64>, Vec<f64>) = disks
+            .centres
+            .iter()
+            .zip(&spanning)
+            .filter(|(_, s)| **s == in_spanning)
+            .map(|(c, _)| (c[0], c[1]))
+            .unzip();
+        // marker diameter of two radii on an 800 pixel wide square plot
+        let trace = Scatter::new(x, y).mode(Mode::Markers).name(name).marker(
+            Marker::new()
+                .size((2.0 * 800.0 / size) as usize)
+                .color(colour)
+                .opacity(0.5),
+        );
+        plot.add_trace(trace);
+    }
+    let layout = Layout::new()
+        .title("Overlapping disks at eta_c")
+        .x_axis(Axis::new().range(vec![0.0, size]))
+        .y_axis(Axis::new().range(vec![0.0, size]));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 800, 800);
+
+    // spanning probability curves for different box sizes cross near eta_c
+    let sizes = vec![10.0, 20.0, 40.0];
+    let eta_vals = (80..=145)
+        .step_by(5)
+        .map(|x| x as f64 / 100.0)
+        .collect::<Vec<f64>>();
+    let mut plot = Plot::new();
+    for &size in &sizes {
+        let probabilities = eta_vals
+            .iter()
+            .map(|&eta| continuum::spanning_probability::<2, _>(size, 1.0, eta, runs, &mut rng))
+            .collect::<Vec<f64>>();
+        let trace = Scatter::new(eta_vals.clone(), probabilities)
+            .mode(Mode::LinesMarkers)
+            .name(format!("L = {}", size).as_str());
+        plot.add_trace(trace);
+    }
+    let layout = Layout::new()
+        .title("Disk Spanning Probability")
+        .x_axis(Axis::new().title("eta"))
+        .y_axis(Axis::new().title("P(spanning)"));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+
+    // critical filling fraction from the density at which spanning first
+    // appears, compared with the site threshold of the matching lattice
+    let cases = [
+        ("disks", 2, DISK_ETA_C, 0.592746),
+        ("spheres", 3, SPHERE_ETA_C, 0.311608),
+    ];
+    let mut plot = Plot::new();
+    for (name, dimension, eta_c, lattice_pc) in cases {
+        let mut x = vec![];
+        let mut y = vec![];
+        for &size in &sizes {
+            let threshold = (0..runs)
+                .map(|_| match dimension {
+                    2 => continuum::spanning_threshold::<2, _>(size, 1.0, &mut rng),
+                    _ => continuum::spanning_threshold::<3, _>(size / 2.0, 1.0, &mut rng),
+                })
+                .sum::<f64>()
+                / runs as f64;
+            let size = if dimension == 2 { size } else { size / 2.0 };
+            println!(
+                "{}, L = {}: eta_c = {:.4}, phi_c = {:.4}",
+                name,
+                size,
+                threshold,
+                continuum::filling_fraction(threshold)
+            );
+            x.push(size);
+            y.push(continuum::filling_fraction(threshold));
+        }
+        println!(
+            "{}: literature eta_c = {}, phi_c = {:.4}; lattice site p_c = {}",
+            name,
+            eta_c,
+            continuum::filling_fraction(eta_c),
+            lattice_pc
+        );
+        let trace = Scatter::new(x, y).mode(Mode::LinesMarkers).name(
+            format!(
+                "{}, phi_c = {:.4}",
+                name,
+                continuum::filling_fraction(eta_c)
+            )
+            .as_str(),
+        );
+        plot.add_trace(trace);
+    }
+    let layout = Layout::new()
+        .title("Filling Fraction at First Spanning")
+        .x_axis(Axis::new().title("L / r").type_(AxisType::Log))
+        .y_axis(Axis::new().title("phi"));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+}
+
+fn explosive_percolation_examples() {
+    use explosive::{Graph, Rule};
+    let mut rng = rand::thread_rng();
+    let rules = [
+        ("random", Rule::Random, 1),
+        ("product rule", Rule::Product, 2),
+        ("sum rule", Rule::Sum, 2),
+    ];
+
+    // growth of the largest cluster on a random graph and on the lattice
+    for (graph, max_edges, name) in [
+        (Graph::Random(1 << 18), 1.0, "Random Graph, N = 2^18"),
+        (Graph::Lattice(256), 2.0, "Square Lattice, L = 256"),
+    ] {
+        let mut plot = Plot::new();
+        for (rule_name, rule, m) in rules {
+            let result = explosive::achlioptas(graph, rule, m, max_edges, &mut rng);
+            let width = result
+                .transition_width(graph.nodes())
+                .map_or("-".to_string(), |w| format!("{:.4}", w));
+            println!(
+                "{}, {}: largest jump = {:.4}, transition width = {}",
+                name,
+                rule_name,
+                result.largest_jump(),
+                width
+            );
+            // every edge is a point, so plot a thousand of them
+            let every = result.edges.len() / 1000 + 1;
+            let trace = Scatter::new(
+                result.edges.into_iter().step_by(every).collect(),
+                result.largest.into_iter().step_by(every).collect(),
+            )
+            .mode(Mode::Lines)
+            .name(rule_name);
+            plot.add_trace(trace);
+        }
+        let layout = Layout::new()
+            .title(format!("Explosive Percolation on a {}", name).as_str())
+            .x_axis(Axis::new().title("edges / N"))
+            .y_axis(Axis::new().title("largest cluster / N"));
+        plot.set_layout(layout);
+        plot.show_image(ImageFormat::PNG, 1000, 800);
+    }
+
+    // best-of-m product rule: more candidates delay and sharpen the transition
+    let graph = Graph::Random(1 << 18);
+    let mut plot = Plot::new();
+    for m in [1, 2, 3, 5] {
+        let result = explosive::achlioptas(graph, Rule::Product, m, 1.0, &mut rng);
+        let every = result.edges.len() / 1000 + 1;
+        let trace = Scatter::new(
+            result.edges.into_iter().step_by(every).collect(),
+            result.largest.into_iter().step_by(every).collect(),
+        )
+        .mode(Mode::Lines)
+        .name(format!("m = {}", m).as_str());
+        plot.add_trace(trace);
+    }
+    let layout = Layout::new()
+        .title("Best-of-m Product Rule, N = 2^18")
+        .x_axis(Axis::new().title("edges / N"))
+        .y_axis(Axis::new().title("largest cluster / N"));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+
+    // the transition window shrinks with N for the product rule only
+    let t = 20;
+    let n_vec = (10..=20).step_by(2).map(|k| 1 << k).collect::<Vec<usize>>();
+    let mut plot = Plot::new();
+    for (rule_name, rule, m) in rules {
+        let mut points = vec![];
+        for &n in &n_vec {
+            let mut widths = vec![];
+            let mut jump = 0.0;
+            for _ in 0..t {
+                let result = explosive::achlioptas(Graph::Random(n), rule, m, 1.0, &mut rng);
+                widths.extend(result.transition_width(n));
+                jump += result.largest_jump() / t as f64;
+            }
+            // runs whose largest cluster stays below N / 2 have no width
+            if widths.is_empty() {
+                println!("{}, N = {}: no transition by 1 edge per node", rule_name, n);
+                continue;
+            }
+            let width = widths.iter().sum::<f64>() / widths.len() as f64;
+            println!(
+                "{}, N = {}: transition width = {:.4} ({} of {} runs), largest jump = {:.4}",
+                rule_name,
+                n,
+                width,
+                widths.len(),
+                t,
+                jump
+            );
+            points.push((n as f64, width));
+        }
+        let exponent = scaling::log_log_slope(&points);
+        let (x, y): (Vec<f64>, Vec<f64>) = points.into_iter().unzip();
+        let trace = Scatter::new(x, y)
+            .mode(Mode::LinesMarkers)
+            .name(format!("{}, slope = {:.3}", rule_name, exponent).as_str());
+        plot.add_trace(trace);
+    }
+    let layout = Layout::new()
+        .title("Transition Width vs N")
+        .x_axis(Axis::new().title("N").type_(AxisType::Log))
+        .y_axis(Axis::new().title("width (edges / N)").type_(AxisType::Log));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+}
+
+fn forest_fire_examples() {
+    use forest_fire::{ForestFire, CRITICAL_DENSITY};
+    let mut rng = rand::thread_rng();
+    let l = 256;
+    let transient = 200;
+
+    // steady state and time series for f/p = 1/1000
+    let mut forest = ForestFire::new(l, 1.0, 0.001);
+    let run = forest.run(transient, 500, &mut rng);
+    std::fs::create_dir_all("results").expect("Could not create results directory");
+    run.save_series("results/forest_fire_series.csv").unwrap();
+    run.save_fire_sizes("results/forest_fire_sizes.csv")
+        .unwrap();
+    PercolationLattice::from_values(&forest.values(), CRITICAL_DENSITY as f32)
+        .plot_lattice("Forest-fire steady state, f/p = 1/1000");
+    let sweeps = (0..run.density.len()).collect::<Vec<usize>>();
+    let burnt = run
+        .burnt
+        .iter()
+        .map(|b| *b as f64 / (l * l) as f64)
+        .collect::<Vec<f64>>();
+    let mut plot = Plot::new();
+    plot.add_trace(
+        Scatter::new(sweeps.clone(), run.density)
+            .mode(Mode::Lines)
+            .name("tree density"),
+    );
+    plot.add_trace(
+        Scatter::new(sweeps, burnt)
+            .mode(Mode::Lines)
+            .name("burnt trees / N"),
+    );
+    let layout = Layout::new()
+        .title("Forest-Fire Time Series, f/p = 1/1000")
+        .x_axis(Axis::new().title("sweep"))
+        .y_axis(Axis::new().title("fraction of sites"));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+
+    // fire-size distributions approach a power law as f/p -> 0, with the
+    // cutoff growing with p/f
+    let mut plot = Plot::new();
+    for theta in [125, 250, 500, 1000] {
+        let mut forest = ForestFire::new(l, 1.0, 1.0 / theta as f64);
+        let run = forest.run(transient, 1000, &mut rng);
+        run.save_fire_sizes(&format!("results/forest_fire_sizes_{}.csv", theta))
+            .unwrap();
+        let density = run.density.iter().sum::<f64>() / run.density.len() as f64;
+        let mean_size = run.fire_sizes.iter().sum::<usize>() as f64 / run.fire_sizes.len() as f64;
+        let distribution = scaling::log_binned(&run.fire_sizes, 5.0);
+        // fit below the cutoff, which lies near p/f
+        let power_law = distribution
+            .iter()
+            .filter(|(s, _)| *s >= 10.0 && *s <= theta as f64 / 2.0)
+            .copied()
+            .collect::<Vec<(f64, f64)>>();
+        let tau = -scaling::log_log_slope(&power_law);
+        println!(
+            "p/f = {}: {} fires, mean density = {:.4} (critical {}), mean fire size = {:.1}, tau = {:.3}",
+            theta,
+            run.fire_sizes.len(),
+            density,
+            CRITICAL_DENSITY,
+            mean_size,
+            tau
+        );
+        let (x, y): (Vec<f64>, Vec<f64>) = distribution.into_iter().unzip();
+        let trace = Scatter::new(x, y)
+            .mode(Mode::LinesMarkers)
+            .name(format!("p/f = {}, tau = {:.3}", theta, tau).as_str());
+        plot.add_trace(trace);
+    }
+    let layout = Layout::new()
+        .title(format!("Fire-Size Distribution, L = {}", l).as_str())
+        .x_axis(Axis::new().title("s").type_(AxisType::Log))
+        .y_axis(Axis::new().title("n(s)").type_(AxisType::Log));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+}
+
+/// Spanning detection, cluster labelling and the cluster-size distribution
+/// of a lattice loaded from experimental data.
+fn analyse_lattice(values: &[Vec<u32>], name: &str) {
+    let (height, width) = (values.len(), values[0].len());
+    let occupied = values.iter().flatten().filter(|v| **v == 1).count();
+    let fraction = occupied as f32 / (width * height) as f32;
+    let lattice = PercolationLattice::from_values(values, fraction);
+    lattice.plot_lattice(
+        format!(
+            "{}, {} x {}, occupied fraction {:.3}",
+            name, width, height, fraction
+        )
+        .as_str(),
+    );
+
+    let sizes = lattice.hoshen_kopelman(true);
+    let spanning = lattice.spanning_cluster();
+    let burning = PercolationLattice::from_values(values, fraction).burning_method();
+    println!(
+        "{}: {} x {}, occupied fraction = {:.4}, {} clusters, largest = {}, spanning = {}",
+        name,
+        width,
+        height,
+        fraction,
+        sizes.len(),
+        sizes.iter().max().unwrap_or(&0),
+        burning.spanning
+    );
+    if let (Some(cluster), Some(distance)) = (spanning, burning.chemical_distance) {
+        let mass = cluster.iter().flatten().filter(|s| **s).count();
+        let decomposition = backbone::decompose(&cluster);
+        println!(
+            "{}: spanning cluster mass = {}, backbone = {}, red bonds = {}, chemical distance = {}",
+            name,
+            mass,
+            decomposition.backbone_mass(),
+            decomposition.red_bonds.len(),
+            distance
+        );
+    }
+
+    let (x, y): (Vec<f64>, Vec<f64>) = scaling::log_binned(&sizes, 5.0).into_iter().unzip();
+    let mut plot = Plot::new();
+    plot.add_trace(Scatter::new(x, y).mode(Mode::LinesMarkers).name(name));
+    let layout = Layout::new()
+        .title(format!("Cluster Size Distribution, {}", name).as_str())
+        .x_axis(Axis::new().title("s").type_(AxisType::Log))
+        .y_axis(Axis::new().title("n(s)").type_(AxisType::Log));
+    plot.set_layout(layout);
+    plot.show_image(ImageFormat::PNG, 1000, 800);
+}
+
+fn import_examples() {
+    // pores appear dark in the micrograph; raising the grey-level threshold
+    // takes the pore space from isolated pores to a spanning network
+    let inputs = [
+        ("data/porous.pgm", 0.5, true),
+        ("data/porous.pgm", 0.58, true),
+        ("data/map.txt", 0.5, false),
+    ];
+    for (path, threshold, dark_occupied) in inputs {
+        match import::load_lattice(Path::new(path), threshold, dark_occupied) {
+            Ok(values) => analyse_lattice(
+                &values,
+                format!("{}, threshold {}", path, threshold).as_str(),
+            ),
+            Err(e) => println!("Could not load {}: {}", path, e),
+        }
+    }
+}
+
+/// Prints `message` with the command-line usage and exits.
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("Usage: Percolation <image or ASCII map> [threshold] [--dark]");
+    std::process::exit(2);
+}
+
 fn main() {
+    // analyse a lattice given on the command line instead of the examples:
+    // <image or ASCII map> [threshold] [--dark], with --dark anywhere
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if !args.is_empty() {
+        let (flags, positional): (Vec<&String>, Vec<&String>) =
+            args.iter().partition(|a| a.starts_with("--"));
+        if let Some(flag) = flags.iter().find(|f| **f != "--dark") {
+            usage_error(&format!("Unknown option {}", flag));
+        }
+        let dark_occupied = !flags.is_empty();
+        let (path, threshold) = match positional[..] {
+            [path] => (path, 0.5),
+            [path, t] => match t.parse::<f64>() {
+                Ok(t) if (0.0..=1.0).contains(&t) => (path, t),
+                _ => usage_error(&format!(
+                    "Invalid threshold {:?}: expected a grey level in [0, 1]",
+                    t
+                )),
+            },
+            _ => usage_error("Expected a file name and at most one threshold"),
+        };
+        match import::load_lattice(Path::new(path), threshold, dark_occupied) {
+            Ok(values) => analyse_lattice(&values, path),
+            Err(e) => eprintln!("Could not load {}: {}", path, e),
+        }
+        return;
+    }
     percolation_examples();
     monte_carlo_examples();
     occupation_probability_examples();
+    invasion_percolation_examples();
+    directed_percolation_examples();
+    fractal_dimension_examples();
+    chemical_distance_examples();
+    backbone_examples();
+    resistor_network_examples();
+    continuum_percolation_examples();
+    explosive_percolation_examples();
+    forest_fire_examples();
+    import_examples();
 }
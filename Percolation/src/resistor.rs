@@ -0,0 +1,232 @@
+/// Square sparse matrix in compressed sparse row format.
+pub struct SparseMatrix {
+    row_start: Vec<usize>,
+    columns: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl SparseMatrix {
+    /// Builds the matrix from (row, column, value) entries given row by row.
+    fn from_rows(rows: Vec<Vec<(usize, f64)>>) -> Self {
+        let mut row_start = vec![0];
+        let mut columns = vec![];
+        let mut values = vec![];
+        for row in rows {
+            for (column, value) in row {
+                columns.push(column);
+                values.push(value);
+            }
+            row_start.push(columns.len());
+        }
+        SparseMatrix {
+            row_start,
+            columns,
+            values,
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.row_start.len() - 1
+    }
+
+    fn multiply(&self, x: &[f64]) -> Vec<f64> {
+        (0..self.size())
+            .map(|row| {
+                (self.row_start[row]..self.row_start[row + 1])
+                    .map(|k| self.values[k] * x[self.columns[k]])
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn diagonal(&self) -> Vec<f64> {
+        (0..self.size())
+            .map(|row| {
+                (self.row_start[row]..self.row_start[row + 1])
+                    .find(|k| self.columns[*k] == row)
+                    .map_or(0.0, |k| self.values[k])
+            })
+            .collect()
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Solves A x = b for a symmetric positive definite A by the conjugate
+/// gradient method with a Jacobi (diagonal) preconditioner, until the
+/// residual is below `tolerance` times |b|. Returns the solution and the
+/// number of iterations.
+pub fn conjugate_gradient(
+    a: &SparseMatrix,
+    b: &[f64],
+    tolerance: f64,
+    max_iterations: usize,
+) -> (Vec<f64>, usize) {
+    let inverse_diagonal = a.diagonal().iter().map(|d| 1.0 / d).collect::<Vec<f64>>();
+    let mut x = vec![0.0; b.len()];
+    let mut r = b.to_vec();
+    let mut z = r
+        .iter()
+        .zip(&inverse_diagonal)
+        .map(|(r, d)| r * d)
+        .collect::<Vec<f64>>();
+    let mut p = z.clone();
+    let mut rz = dot(&r, &z);
+    let target = tolerance * dot(b, b).sqrt();
+    for iteration in 0..max_iterations {
+        if dot(&r, &r).sqrt() <= target {
+            return (x, iteration);
+        }
+        let ap = a.multiply(&p);
+        let alpha = rz / dot(&p, &ap);
+        for k in 0..x.len() {
+            x[k] += alpha * p[k];
+            r[k] -= alpha * ap[k];
+            z[k] = r[k] * inverse_diagonal[k];
+        }
+        let rz_next = dot(&r, &z);
+        let beta = rz_next / rz;
+        rz = rz_next;
+        for k in 0..p.len() {
+            p[k] = z[k] + beta * p[k];
+        }
+    }
+    (x, max_iterations)
+}
+
+pub struct ResistorNetwork {
+    /// Total current for a unit voltage between the first and last rows.
+    pub conductance: f64,
+    /// Potential of every cluster site, `None` outside the cluster.
+    pub voltages: Vec<Vec<Option<f64>>>,
+    /// Absolute current through every bond of the cluster.
+    pub currents: Vec<f64>,
+    /// Conjugate gradient iterations needed for the potentials.
+    pub iterations: usize,
+}
+
+fn neighbours(l: usize, i: usize, j: usize) -> impl Iterator<Item = (usize, usize)> {
+    [
+        (i.wrapping_sub(1), j),
+        (i + 1, j),
+        (i, j.wrapping_sub(1)),
+        (i, j + 1),
+    ]
+    .into_iter()
+    .filter(move |&(ni, nj)| ni < l && nj < l)
+}
+
+impl ResistorNetwork {
+    /// Current flowing through each site: the larger of its total inflow and
+    /// outflow, which are equal except on the rows held at fixed potential.
+    pub fn site_currents(&self) -> Vec<Vec<f64>> {
+        let l = self.voltages.len();
+        self.voltages
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(j, v)| {
+                        let Some(v) = v else {
+                            return 0.0;
+                        };
+                        let (mut inflow, mut outflow) = (0.0, 0.0);
+                        for (ni, nj) in neighbours(l, i, j) {
+                            if let Some(w) = self.voltages[ni][nj] {
+                                inflow += (w - v).max(0.0);
+                                outflow += (v - w).max(0.0);
+                            }
+                        }
+                        f64::max(inflow, outflow)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Treats every bond between neighbouring sites of the spanning cluster as a
+/// unit resistor, holds the first row at potential 1 and the last row at 0,
+/// and solves Kirchhoff's current law for the potentials of the other sites.
+pub fn solve(cluster: &[Vec<bool>]) -> ResistorNetwork {
+    let l = cluster.len();
+    let fixed = |i: usize| {
+        if i == 0 {
+            Some(1.0)
+        } else if i == l - 1 {
+            Some(0.0)
+        } else {
+            None
+        }
+    };
+    let mut index = vec![vec![usize::MAX; l]; l];
+    let mut unknowns = vec![];
+    for (i, row) in cluster.iter().enumerate() {
+        for (j, site) in row.iter().enumerate() {
+            if *site && fixed(i).is_none() {
+                index[i][j] = unknowns.len();
+                unknowns.push((i, j));
+            }
+        }
+    }
+
+    // sum over neighbours of (V_i - V_j) = 0, with the fixed potentials moved
+    // to the right-hand side
+    let mut rows = Vec::with_capacity(unknowns.len());
+    let mut b = vec![0.0; unknowns.len()];
+    for (k, &(i, j)) in unknowns.iter().enumerate() {
+        let mut row = vec![];
+        let mut degree = 0.0;
+        for (ni, nj) in neighbours(l, i, j).filter(|&(ni, nj)| cluster[ni][nj]) {
+            degree += 1.0;
+            match fixed(ni) {
+                Some(v) => b[k] += v,
+                None => row.push((index[ni][nj], -1.0)),
+            }
+        }
+        row.push((k, degree));
+        rows.push(row);
+    }
+    let matrix = SparseMatrix::from_rows(rows);
+    let (solution, iterations) = conjugate_gradient(&matrix, &b, 1e-10, 10 * unknowns.len() + 100);
+
+    let mut voltages = vec![vec![None; l]; l];
+    for (i, row) in cluster.iter().enumerate() {
+        for (j, site) in row.iter().enumerate() {
+            if *site {
+                voltages[i][j] = fixed(i).or_else(|| Some(solution[index[i][j]]));
+            }
+        }
+    }
+    let mut currents = vec![];
+    let mut conductance = 0.0;
+    for i in 0..l {
+        for j in 0..l {
+            let Some(v) = voltages[i][j] else {
+                continue;
+            };
+            for (ni, nj) in [(i + 1, j), (i, j + 1)] {
+                if let Some(w) = voltages
+                    .get(ni)
+                    .and_then(|row| row.get(nj))
+                    .copied()
+                    .flatten()
+                {
+                    currents.push((v - w).abs());
+                    if i == 0 && ni == 1 {
+                        conductance += v - w;
+                    }
+                }
+            }
+        }
+    }
+    ResistorNetwork {
+        conductance,
+        voltages,
+        currents,
+        iterations,
+    }
+}
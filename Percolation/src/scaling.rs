@@ -0,0 +1,130 @@
+use rand::Rng;
+
+/// Least-squares slope of ln(y) against ln(x), i.e. the exponent of y ~ x^a.
+pub fn log_log_slope(points: &[(f64, f64)]) -> f64 {
+    let logs = points
+        .iter()
+        .map(|(x, y)| (x.ln(), y.ln()))
+        .collect::<Vec<(f64, f64)>>();
+    let n = logs.len() as f64;
+    let mean_x = logs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = logs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance = logs
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum::<f64>();
+    let variance = logs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>();
+    covariance / variance
+}
+
+/// Distribution of positive sizes in logarithmic bins, `bins_per_decade`
+/// per factor of ten. Returns (geometric bin centre, probability density)
+/// pairs for the non-empty bins, so that a power law n(s) ~ s^-tau appears
+/// as a straight line of slope -tau on log-log axes.
+pub fn log_binned(sizes: &[usize], bins_per_decade: f64) -> Vec<(f64, f64)> {
+    let mut counts = vec![];
+    for &s in sizes.iter().filter(|s| **s > 0) {
+        let bin = ((s as f64).log10() * bins_per_decade) as usize;
+        if counts.len() <= bin {
+            counts.resize(bin + 1, 0);
+        }
+        counts[bin] += 1;
+    }
+    let total = counts.iter().sum::<usize>() as f64;
+    counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .map(|(bin, count)| {
+            let start = 10f64.powf(bin as f64 / bins_per_decade);
+            let end = 10f64.powf((bin + 1) as f64 / bins_per_decade);
+            // only integer sizes fall in a bin, so its width is their number
+            let width = (end.ceil() - start.ceil()).max(1.0);
+            ((start * end).sqrt(), *count as f64 / (total * width))
+        })
+        .collect()
+}
+
+/// Powers of two from `min_radius` up to `max_radius`. Scales below a few
+/// lattice spacings are dominated by lattice effects, so fits of fractal
+/// dimensions should start above them.
+pub fn doubling_radii(min_radius: usize, max_radius: usize) -> Vec<usize> {
+    let mut radii = vec![];
+    let mut r = min_radius.next_power_of_two();
+    while r <= max_radius {
+        radii.push(r);
+        r *= 2;
+    }
+    radii
+}
+
+/// Summed-area table of the cluster, so that the mass inside any box is
+/// found in constant time.
+fn summed_area(cluster: &[Vec<bool>]) -> Vec<Vec<usize>> {
+    let l = cluster.len();
+    let mut table = vec![vec![0; l + 1]; l + 1];
+    for i in 0..l {
+        for j in 0..l {
+            table[i + 1][j + 1] =
+                cluster[i][j] as usize + table[i][j + 1] + table[i + 1][j] - table[i][j];
+        }
+    }
+    table
+}
+
+/// Sandbox mass-radius relation M(r): mean number of cluster sites in a box
+/// of half-width r centred on a random cluster site, using only centres
+/// whose boxes fit inside the lattice. Returns (r, M(r)) pairs, from which
+/// the fractal dimension is the slope of ln M against ln r.
+pub fn mass_radius<R: Rng>(
+    cluster: &[Vec<bool>],
+    radii: &[usize],
+    centres: usize,
+    rng: &mut R,
+) -> Vec<(f64, f64)> {
+    let l = cluster.len();
+    let table = summed_area(cluster);
+    let sites = (0..l)
+        .flat_map(|i| (0..l).map(move |j| (i, j)))
+        .filter(|&(i, j)| cluster[i][j])
+        .collect::<Vec<(usize, usize)>>();
+    let mut points = vec![];
+    for &r in radii {
+        let inside = sites
+            .iter()
+            .filter(|&&(i, j)| i >= r && j >= r && i + r < l && j + r < l)
+            .collect::<Vec<&(usize, usize)>>();
+        if inside.is_empty() {
+            continue;
+        }
+        let mut total = 0;
+        for _ in 0..centres {
+            let (i, j) = *inside[rng.gen_range(0..inside.len())];
+            let (top, left, bottom, right) = (i - r, j - r, i + r + 1, j + r + 1);
+            total +=
+                table[bottom][right] + table[top][left] - table[top][right] - table[bottom][left];
+        }
+        points.push((r as f64, total as f64 / centres as f64));
+    }
+    points
+}
+
+/// Average of several (x, y) curves point by point, matching points by x,
+/// since `mass_radius` leaves out the radii without any centres. Each mean
+/// is over the curves that have the point.
+pub fn mean_curve(curves: &[Vec<(f64, f64)>]) -> Vec<(f64, f64)> {
+    let mut sums: Vec<(f64, f64, usize)> = vec![];
+    for &(x, y) in curves.iter().flatten() {
+        match sums.iter_mut().find(|(sum_x, _, _)| *sum_x == x) {
+            Some((_, sum_y, n)) => {
+                *sum_y += y;
+                *n += 1;
+            }
+            None => sums.push((x, y, 1)),
+        }
+    }
+    sums.sort_by(|a, b| a.0.total_cmp(&b.0));
+    sums.into_iter()
+        .map(|(x, y, n)| (x, y / n as f64))
+        .collect()
+}
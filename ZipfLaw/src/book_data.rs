@@ -1,6 +1,45 @@
 pub mod book_data {
-  use csv::Writer;
+  use csv::{Reader, Writer};
+  use serde::{Deserialize, Serialize};
+  use std::collections::HashMap;
   use std::error::Error;
+  use std::fs::File;
+  use std::io::{BufReader, BufWriter, Write};
+  use std::path::Path;
+
+  use crate::sources::sources::{BookMetadata, SourceFormat};
+
+  /// Suffix of the file that `save_results` writes next to a CSV table to
+  /// hold its header, e.g. "book_word1.meta.json" for "book_word1.csv".
+  pub const SIDECAR_SUFFIX: &str = ".meta.json";
+
+  fn sidecar_path(file_path: &str) -> String {
+    format!("{}{}", file_path.strip_suffix(".csv").unwrap_or(file_path), SIDECAR_SUFFIX)
+  }
+
+  /// One line of the rank-frequency table as stored in CSV and JSON files.
+  #[derive(Debug, Serialize, Deserialize)]
+  pub struct BookRow {
+    pub rank: i64,
+    pub word: String,
+    pub count: i64,
+    pub frequency: f64,
+  }
+
+  /// What a saved table was counted from: the counting unit, as in
+  /// `NgramUnit::label`, and the metadata of the book.
+  #[derive(Clone, Debug, Serialize, Deserialize)]
+  pub struct TableHeader {
+    pub unit: String,
+    pub metadata: BookMetadata,
+  }
+
+  #[derive(Serialize, Deserialize)]
+  struct JsonTable {
+    #[serde(flatten)]
+    header: TableHeader,
+    rows: Vec<BookRow>,
+  }
 
   pub struct BookData {
     pub ranks: Vec<i64>,
@@ -10,24 +49,97 @@ pub mod book_data {
   }
 
   impl BookData {
-    pub fn save_results(&self, file_path: &str) -> Result<(), Box<dyn Error>> {
-        let mut wtr = Writer::from_path(file_path)?;
+    /// Builds the rank-frequency table from word counts, most frequent first.
+    pub fn from_counts(word_count: HashMap<String, i64>) -> BookData {
+        let total_words: i64 = word_count.values().sum();
+        let mut word_count_vec: Vec<(String, i64)> = word_count.into_iter().collect();
+        word_count_vec.sort_by_key(|w| std::cmp::Reverse(w.1));
+        let frequencies = word_count_vec.iter().map(|(_, y)| *y as f64 / total_words as f64).collect::<Vec<f64>>();
+        let ranks = (1..=word_count_vec.len() as i64).collect::<Vec<i64>>();
+        let (words, counts) = word_count_vec.into_iter().unzip();
+        BookData {
+            ranks,
+            words,
+            counts,
+            frequencies,
+        }
+    }
 
-        // Write the header
-        wtr.write_record(&["rank", "word", "count", "frequency"])?;
-
-        // Write the data
-        for i in 0..self.ranks.len() {
-            wtr.write_record(&[
-                self.ranks[i].to_string(),
-                self.words[i].clone(),
-                self.counts[i].to_string(),
-                self.frequencies[i].to_string(),
-            ])?;
+    pub fn rows(&self) -> Vec<BookRow> {
+        (0..self.ranks.len())
+            .map(|i| BookRow {
+                rank: self.ranks[i],
+                word: self.words[i].clone(),
+                count: self.counts[i],
+                frequency: self.frequencies[i],
+            })
+            .collect()
+    }
+
+    pub fn from_rows(rows: Vec<BookRow>) -> BookData {
+        let mut bd = BookData {
+            ranks: Vec::with_capacity(rows.len()),
+            words: Vec::with_capacity(rows.len()),
+            counts: Vec::with_capacity(rows.len()),
+            frequencies: Vec::with_capacity(rows.len()),
+        };
+        for row in rows {
+            bd.ranks.push(row.rank);
+            bd.words.push(row.word);
+            bd.counts.push(row.count);
+            bd.frequencies.push(row.frequency);
         }
+        bd
+    }
 
+    /// Writes the table as plain CSV and its header as JSON to the sidecar
+    /// file next to it.
+    pub fn save_results(&self, file_path: &str, header: &TableHeader) -> Result<(), Box<dyn Error>> {
+        let mut wtr = Writer::from_path(file_path)?;
+        // the header rank,word,count,frequency comes from the field names
+        for row in self.rows() {
+            wtr.serialize(row)?;
+        }
         wtr.flush()?;
+        let mut writer = BufWriter::new(File::create(sidecar_path(file_path))?);
+        serde_json::to_writer(&mut writer, header)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a table written by `save_results`. Tables without a sidecar
+    /// file, e.g. from older versions, are taken to count single words of a
+    /// book that nothing is known about.
+    pub fn load_results(file_path: &str) -> Result<(BookData, TableHeader), Box<dyn Error>> {
+        let mut rdr = Reader::from_path(file_path)?;
+        let rows = rdr
+            .deserialize()
+            .collect::<Result<Vec<BookRow>, csv::Error>>()
+            .map_err(|error| format!("{} is not a count table: {}", file_path, error))?;
+        let sidecar = sidecar_path(file_path);
+        let header = if Path::new(&sidecar).exists() {
+            serde_json::from_reader(BufReader::new(File::open(&sidecar)?))?
+        } else {
+            TableHeader {
+                unit: "word1".to_string(),
+                metadata: BookMetadata::new(SourceFormat::Text),
+            }
+        };
+        Ok((BookData::from_rows(rows), header))
+    }
+
+    pub fn save_json(&self, file_path: &str, header: &TableHeader) -> Result<(), Box<dyn Error>> {
+        let mut writer = BufWriter::new(File::create(file_path)?);
+        serde_json::to_writer(&mut writer, &JsonTable { header: header.clone(), rows: self.rows() })?;
+        writer.flush()?;
         Ok(())
     }
+
+    pub fn load_json(file_path: &str) -> Result<(BookData, TableHeader), Box<dyn Error>> {
+        let reader = BufReader::new(File::open(file_path)?);
+        let table: JsonTable = serde_json::from_reader(reader)
+            .map_err(|error| format!("{} is not a count table saved by `count`: {}", file_path, error))?;
+        Ok((BookData::from_rows(table.rows), table.header))
+    }
   }
 }
\ No newline at end of file
@@ -0,0 +1,97 @@
+pub mod cache {
+    use std::error::Error;
+    use std::fs::{self, File};
+    use std::io::{self, BufReader, BufWriter, Read, Write};
+    use std::path::{Path, PathBuf};
+
+    use serde::{Deserialize, Serialize};
+    use sha2::{Digest, Sha256};
+
+    use crate::book_data::book_data::{BookData, BookRow};
+    use crate::ngrams::ngrams::NgramUnit;
+    use crate::sources::sources::BookMetadata;
+    use crate::tokenizer::tokenizer::TokenizerOptions;
+
+    #[derive(Serialize, Deserialize)]
+    struct CacheEntry {
+        metadata: BookMetadata,
+        rows: Vec<BookRow>,
+    }
+
+    /// Directory of previously computed counts, keyed by the SHA-256 of the
+    /// book file together with the tokenizer settings and counting unit, so
+    /// that renamed or moved books are still found and edited ones are not.
+    pub struct CountCache {
+        dir: PathBuf,
+    }
+
+    impl CountCache {
+        pub fn new(dir: PathBuf) -> CountCache {
+            CountCache { dir }
+        }
+
+        pub fn key(
+            file_path: &Path,
+            options: &TokenizerOptions,
+            unit: NgramUnit,
+        ) -> io::Result<String> {
+            let mut hasher = Sha256::new();
+            let mut file = File::open(file_path)?;
+            let mut buffer = vec![0u8; 1 << 16];
+            loop {
+                let read = file.read(&mut buffer)?;
+                if read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..read]);
+            }
+            // counts also change with the program version, e.g. the tokenizer
+            hasher.update(
+                format!(
+                    "{}|{:?}|{}",
+                    env!("CARGO_PKG_VERSION"),
+                    options,
+                    unit.label()
+                )
+                .as_bytes(),
+            );
+            Ok(hasher
+                .finalize()
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect())
+        }
+
+        fn path(&self, key: &str) -> PathBuf {
+            self.dir.join(format!("{}.json", key))
+        }
+
+        /// Cached counts, or `None` if missing or unreadable.
+        pub fn get(&self, key: &str) -> Option<(BookData, BookMetadata)> {
+            let reader = BufReader::new(File::open(self.path(key)).ok()?);
+            let entry: CacheEntry = serde_json::from_reader(reader).ok()?;
+            Some((BookData::from_rows(entry.rows), entry.metadata))
+        }
+
+        pub fn put(
+            &self,
+            key: &str,
+            bd: &BookData,
+            metadata: &BookMetadata,
+        ) -> Result<(), Box<dyn Error>> {
+            fs::create_dir_all(&self.dir)?;
+            let entry = CacheEntry {
+                metadata: metadata.clone(),
+                rows: bd.rows(),
+            };
+            // write to a temporary file first so that an interrupted run
+            // never leaves a truncated entry behind
+            let temporary = self.dir.join(format!("{}.tmp", key));
+            let mut writer = BufWriter::new(File::create(&temporary)?);
+            serde_json::to_writer(&mut writer, &entry)?;
+            writer.flush()?;
+            fs::rename(temporary, self.path(key))?;
+            Ok(())
+        }
+    }
+}
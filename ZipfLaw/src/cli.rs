@@ -0,0 +1,169 @@
+pub mod cli {
+    use std::path::PathBuf;
+
+    use clap::{Args, Parser, Subcommand, ValueEnum};
+
+    use crate::ngrams::ngrams::NgramUnit;
+    use crate::tokenizer::tokenizer::{ApostropheMode, DigitMode, HyphenMode, TokenizerOptions};
+
+    /// Rank-frequency analysis of word statistics in books.
+    #[derive(Parser)]
+    #[command(version, about)]
+    pub struct Cli {
+        #[command(subcommand)]
+        pub command: Command,
+    }
+
+    #[derive(Subcommand)]
+    pub enum Command {
+        /// Count words or n-grams and save the rank-frequency tables as CSV.
+        Count(CountArgs),
+        /// Plot rank-frequency curves against the theoretical Zipf law.
+        Plot(PlotArgs),
+        /// Fit statistical models to every input.
+        Fit(FitArgs),
+        /// Compare Zipf-Mandelbrot fits across inputs, e.g. translations.
+        Compare(CompareArgs),
+    }
+
+    /// Options shared by every subcommand.
+    #[derive(Args)]
+    pub struct CommonArgs {
+        /// Input files, directories or glob patterns such as "books/*.txt.gz";
+        /// CSV or JSON tables saved by `count` are read back as they are.
+        #[arg(required = true)]
+        pub inputs: Vec<String>,
+        /// Directory for results, created if it does not exist.
+        #[arg(short, long, default_value = "results")]
+        pub output: PathBuf,
+        /// Recount every book instead of reusing counts cached in the output
+        /// directory.
+        #[arg(long)]
+        pub no_cache: bool,
+        #[command(flatten)]
+        pub tokenizer: TokenizerArgs,
+    }
+
+    #[derive(Args)]
+    pub struct TokenizerArgs {
+        #[arg(long, value_enum, default_value_t = ApostropheMode::Keep)]
+        pub apostrophes: ApostropheMode,
+        #[arg(long, value_enum, default_value_t = HyphenMode::Keep)]
+        pub hyphens: HyphenMode,
+        #[arg(long, value_enum, default_value_t = DigitMode::Drop)]
+        pub digits: DigitMode,
+        /// Keep upper and lower case spellings as different words.
+        #[arg(long)]
+        pub no_case_fold: bool,
+        /// Skip NFC normalisation of the text.
+        #[arg(long)]
+        pub no_normalize: bool,
+    }
+
+    impl TokenizerArgs {
+        pub fn options(&self) -> TokenizerOptions {
+            TokenizerOptions {
+                apostrophes: self.apostrophes,
+                hyphens: self.hyphens,
+                digits: self.digits,
+                case_fold: !self.no_case_fold,
+                normalize: !self.no_normalize,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, ValueEnum)]
+    pub enum UnitKind {
+        Word,
+        Char,
+    }
+
+    #[derive(Args)]
+    pub struct UnitArgs {
+        /// Count words or letters.
+        #[arg(long, value_enum, default_value_t = UnitKind::Word)]
+        pub unit: UnitKind,
+        /// Length of the counted n-grams, at least 1.
+        #[arg(short, long, default_value_t = 1, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        pub n: usize,
+    }
+
+    impl UnitArgs {
+        pub fn unit(&self) -> NgramUnit {
+            match self.unit {
+                UnitKind::Word => NgramUnit::Word(self.n),
+                UnitKind::Char => NgramUnit::Character(self.n),
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq, ValueEnum)]
+    pub enum TableFormat {
+        Csv,
+        Json,
+    }
+
+    #[derive(Args)]
+    pub struct CountArgs {
+        #[command(flatten)]
+        pub common: CommonArgs,
+        #[command(flatten)]
+        pub unit: UnitArgs,
+        /// Format of the saved rank-frequency tables.
+        #[arg(long, value_enum, default_value_t = TableFormat::Csv)]
+        pub format: TableFormat,
+    }
+
+    #[derive(Clone, Copy, PartialEq, ValueEnum)]
+    pub enum Scale {
+        Lin,
+        Log,
+        Both,
+    }
+
+    #[derive(Args)]
+    pub struct PlotArgs {
+        #[command(flatten)]
+        pub common: CommonArgs,
+        #[command(flatten)]
+        pub unit: UnitArgs,
+        #[arg(long, value_enum, default_value_t = Scale::Both)]
+        pub scale: Scale,
+    }
+
+    #[derive(Clone, Copy, PartialEq, ValueEnum)]
+    pub enum Model {
+        /// Normalised Zipf-Mandelbrot law by maximum likelihood.
+        ZipfMandelbrot,
+        /// Discrete power law of the word counts with goodness-of-fit tests.
+        PowerLaw,
+        /// Heaps' law of vocabulary growth.
+        Heaps,
+    }
+
+    #[derive(Args)]
+    pub struct FitArgs {
+        #[command(flatten)]
+        pub common: CommonArgs,
+        #[command(flatten)]
+        pub unit: UnitArgs,
+        /// Models to fit; may be repeated.
+        #[arg(short, long, value_enum, default_values_t = [Model::ZipfMandelbrot])]
+        pub model: Vec<Model>,
+        /// Synthetic data sets for the power-law bootstrap p-value.
+        #[arg(long, default_value_t = 100)]
+        pub bootstrap: usize,
+        /// Random shufflings averaged in the Heaps' law analysis.
+        #[arg(long, default_value_t = 10)]
+        pub shuffles: usize,
+    }
+
+    #[derive(Args)]
+    pub struct CompareArgs {
+        #[command(flatten)]
+        pub common: CommonArgs,
+        /// Resampled texts used for the confidence intervals of a and b.
+        #[arg(long, default_value_t = 100)]
+        pub bootstrap: usize,
+    }
+}
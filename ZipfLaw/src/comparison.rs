@@ -0,0 +1,286 @@
+pub mod comparison {
+    use std::collections::HashMap;
+    use std::error::Error;
+
+    use csv::Writer;
+    use plotters::prelude::*;
+    use rand::distributions::{Distribution, WeightedIndex};
+    use rand::seq::index::sample;
+    use rand::Rng;
+
+    use crate::book_data::book_data::BookData;
+    use crate::fitting::fitting::fit_zipf_mandelbrot;
+    use crate::sources::sources::BookMetadata;
+    use crate::zipf_mandelbrot_result::zipf_mandelbrot_result::ZipfMandelbrotResult;
+
+    /// Zipf-Mandelbrot fit of one text with bootstrap confidence intervals.
+    pub struct LanguageSummary {
+        pub fit: ZipfMandelbrotResult,
+        pub metadata: BookMetadata,
+        /// 95% percentile bootstrap intervals of `a` and `b`.
+        pub a_interval: (f64, f64),
+        pub b_interval: (f64, f64),
+    }
+
+    /// Extracts a parameter estimate and its interval from a summary.
+    type Parameter = fn(&LanguageSummary) -> (f64, (f64, f64));
+
+    fn percentile(sorted: &[f64], q: f64) -> f64 {
+        let position = q * (sorted.len() - 1) as f64;
+        let (low, high) = (position.floor() as usize, position.ceil() as usize);
+        sorted[low] + (position - low as f64) * (sorted[high] - sorted[low])
+    }
+
+    /// Fits the book and refits `samples` texts of the same length drawn with
+    /// replacement from its word distribution to get 95% intervals of a and b.
+    /// A book without words has nothing to fit and is an error.
+    pub fn summarise<R: Rng>(
+        bd: &BookData,
+        language: &str,
+        metadata: BookMetadata,
+        samples: usize,
+        rng: &mut R,
+    ) -> Result<LanguageSummary, Box<dyn Error>> {
+        let total: i64 = bd.counts.iter().sum();
+        if total == 0 {
+            return Err(format!("{} has no words to compare", language).into());
+        }
+        let fit = fit_zipf_mandelbrot(bd, language);
+        let words = WeightedIndex::new(&bd.counts)?;
+        let mut a_values = Vec::with_capacity(samples);
+        let mut b_values = Vec::with_capacity(samples);
+        for _ in 0..samples {
+            let mut resampled = vec![0_i64; bd.counts.len()];
+            for _ in 0..total {
+                resampled[words.sample(rng)] += 1;
+            }
+            let word_count = resampled
+                .into_iter()
+                .enumerate()
+                .filter(|(_, c)| *c > 0)
+                .map(|(i, c)| (bd.words[i].clone(), c))
+                .collect::<HashMap<String, i64>>();
+            let refit = fit_zipf_mandelbrot(&BookData::from_counts(word_count), language);
+            a_values.push(refit.a);
+            b_values.push(refit.b);
+        }
+        a_values.sort_by(|x, y| x.total_cmp(y));
+        b_values.sort_by(|x, y| x.total_cmp(y));
+        let interval = |values: &[f64], estimate: f64| {
+            if values.is_empty() {
+                (estimate, estimate)
+            } else {
+                (percentile(values, 0.025), percentile(values, 0.975))
+            }
+        };
+        Ok(LanguageSummary {
+            a_interval: interval(&a_values, fit.a),
+            b_interval: interval(&b_values, fit.b),
+            fit,
+            metadata,
+        })
+    }
+
+    /// Random subsample of `n_tokens` tokens drawn without replacement, so
+    /// that texts of different length can be compared at equal length.
+    pub fn subsample<R: Rng>(bd: &BookData, n_tokens: usize, rng: &mut R) -> BookData {
+        let total: i64 = bd.counts.iter().sum();
+        if total == 0 || n_tokens == 0 {
+            return BookData::from_counts(HashMap::new());
+        }
+        let mut positions = sample(rng, total as usize, n_tokens.min(total as usize)).into_vec();
+        positions.sort_unstable();
+        let mut word_count = HashMap::new();
+        let mut word = 0;
+        let mut word_end = bd.counts[0] as usize;
+        for position in positions {
+            while position >= word_end {
+                word += 1;
+                word_end += bd.counts[word] as usize;
+            }
+            *word_count.entry(bd.words[word].clone()).or_insert(0) += 1;
+        }
+        BookData::from_counts(word_count)
+    }
+
+    pub fn save_csv(summaries: &[LanguageSummary], file_path: &str) -> Result<(), Box<dyn Error>> {
+        let mut wtr = Writer::from_path(file_path)?;
+        wtr.write_record([
+            "book",
+            "title",
+            "language",
+            "a",
+            "a_std_err",
+            "a_low",
+            "a_high",
+            "b",
+            "b_std_err",
+            "b_low",
+            "b_high",
+            "log_likelihood",
+            "ks_distance",
+            "total_words",
+            "vocabulary_size",
+        ])?;
+        for s in summaries {
+            wtr.write_record(&[
+                s.fit.language.clone(),
+                s.metadata.title.clone().unwrap_or_default(),
+                s.metadata.language.clone().unwrap_or_default(),
+                s.fit.a.to_string(),
+                s.fit.a_std_err.to_string(),
+                s.a_interval.0.to_string(),
+                s.a_interval.1.to_string(),
+                s.fit.b.to_string(),
+                s.fit.b_std_err.to_string(),
+                s.b_interval.0.to_string(),
+                s.b_interval.1.to_string(),
+                s.fit.log_likelihood.to_string(),
+                s.fit.ks_distance.to_string(),
+                s.fit.total_words.to_string(),
+                s.fit.vocabulary_size.to_string(),
+            ])?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    pub fn markdown_table(summaries: &[LanguageSummary]) -> String {
+        let mut table = String::from(
+            "| book | language | a | a 95% CI | b | b 95% CI | KS distance | N | V |\n|---|---|---|---|---|---|---|---|---|\n",
+        );
+        for s in summaries {
+            table.push_str(&format!(
+                "| {} | {} | {:.4} | [{:.4}, {:.4}] | {:.3} | [{:.3}, {:.3}] | {:.4} | {} | {} |\n",
+                s.fit.language,
+                s.metadata.language.as_deref().unwrap_or("-"),
+                s.fit.a,
+                s.a_interval.0,
+                s.a_interval.1,
+                s.fit.b,
+                s.b_interval.0,
+                s.b_interval.1,
+                s.fit.ks_distance,
+                s.fit.total_words,
+                s.fit.vocabulary_size
+            ));
+        }
+        table
+    }
+
+    /// Estimates of a (top) and b (bottom) per language with their intervals.
+    pub fn plot_parameters(summaries: &[LanguageSummary], file_path: &str) {
+        let root = BitMapBackend::new(file_path, (800, 800)).into_drawing_area();
+        root.fill(&WHITE).unwrap();
+        let panels = root.split_evenly((2, 1));
+        let n = summaries.len();
+        let parameters: [(&str, Parameter); 2] = [
+            ("a", |s| (s.fit.a, s.a_interval)),
+            ("b", |s| (s.fit.b, s.b_interval)),
+        ];
+        for (panel, (name, value)) in panels.iter().zip(parameters) {
+            let low = summaries
+                .iter()
+                .map(|s| value(s).0.min(value(s).1 .0))
+                .fold(f64::MAX, f64::min);
+            let high = summaries
+                .iter()
+                .map(|s| value(s).0.max(value(s).1 .1))
+                .fold(f64::MIN, f64::max);
+            let pad = 0.1 * (high - low).max(1e-3);
+            let mut chart = ChartBuilder::on(panel)
+                .caption(
+                    format!("Zipf-Mandelbrot {}", name),
+                    ("Arial", 30).into_font(),
+                )
+                .margin(10)
+                .x_label_area_size(40)
+                .y_label_area_size(60)
+                .build_cartesian_2d(
+                    (0..n.saturating_sub(1)).into_segmented(),
+                    (low - pad)..(high + pad),
+                )
+                .unwrap();
+            chart
+                .configure_mesh()
+                .x_labels(n)
+                .x_label_formatter(&|x| match x {
+                    SegmentValue::CenterOf(i) if *i < n => summaries[*i].fit.language.clone(),
+                    _ => String::new(),
+                })
+                .y_desc(name)
+                .draw()
+                .unwrap();
+            chart
+                .draw_series(summaries.iter().enumerate().map(|(i, s)| {
+                    let (estimate, (low, high)) = value(s);
+                    ErrorBar::new_vertical(
+                        SegmentValue::CenterOf(i),
+                        low,
+                        estimate,
+                        high,
+                        BLUE.filled(),
+                        10,
+                    )
+                }))
+                .unwrap();
+        }
+    }
+
+    /// Rank-frequency curves of all texts in log-log scale, each subsampled to
+    /// the length of the shortest text.
+    pub fn plot_rank_frequency<R: Rng>(books: &[(String, BookData)], file_path: &str, rng: &mut R) {
+        let length = books
+            .iter()
+            .map(|(_, bd)| bd.counts.iter().sum::<i64>())
+            .min()
+            .unwrap_or(0) as usize;
+        let subsampled = books
+            .iter()
+            .map(|(name, bd)| (name, subsample(bd, length, rng)))
+            .collect::<Vec<(&String, BookData)>>();
+        let max_rank = subsampled
+            .iter()
+            .map(|(_, bd)| bd.ranks.len())
+            .max()
+            .unwrap_or(1) as i64
+            + 1;
+        let root = BitMapBackend::new(file_path, (800, 600)).into_drawing_area();
+        root.fill(&WHITE).unwrap();
+        let mut chart = ChartBuilder::on(&root)
+            .caption(
+                format!("Rank-frequency at N = {}", length),
+                ("Arial", 40).into_font(),
+            )
+            .margin(5)
+            .x_label_area_size(40)
+            .y_label_area_size(40)
+            .build_cartesian_2d((1..max_rank).log_scale(), (0.000001..1_f64).log_scale())
+            .unwrap();
+        chart
+            .configure_mesh()
+            .x_desc("Rank log")
+            .y_desc("Frequency log")
+            .draw()
+            .unwrap();
+        for (i, (name, bd)) in subsampled.iter().enumerate() {
+            let color = Palette99::pick(i).to_rgba();
+            chart
+                .draw_series(LineSeries::new(
+                    bd.ranks.iter().copied().zip(bd.frequencies.iter().copied()),
+                    color.stroke_width(2),
+                ))
+                .unwrap()
+                .label(name.as_str())
+                .legend(move |(x, y)| {
+                    PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2))
+                });
+        }
+        chart
+            .configure_series_labels()
+            .border_style(BLACK)
+            .label_font(("Arial", 20).into_font())
+            .draw()
+            .unwrap();
+    }
+}
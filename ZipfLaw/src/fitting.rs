@@ -0,0 +1,195 @@
+pub mod fitting {
+    use crate::book_data::book_data::BookData;
+    use crate::zipf_mandelbrot_result::zipf_mandelbrot_result::ZipfMandelbrotResult;
+
+    /// Minimises `f` with the Nelder-Mead simplex method starting from `start`,
+    /// using `steps` as the initial simplex size along each coordinate.
+    pub fn nelder_mead<F: Fn(&[f64]) -> f64>(
+        f: F,
+        start: &[f64],
+        steps: &[f64],
+        tolerance: f64,
+        max_iterations: usize,
+    ) -> Vec<f64> {
+        let n = start.len();
+        let mut simplex = vec![start.to_vec()];
+        for i in 0..n {
+            let mut vertex = start.to_vec();
+            vertex[i] += steps[i];
+            simplex.push(vertex);
+        }
+        let mut values = simplex.iter().map(|v| f(v)).collect::<Vec<f64>>();
+
+        for _ in 0..max_iterations {
+            let mut order = (0..=n).collect::<Vec<usize>>();
+            order.sort_by(|i, j| values[*i].total_cmp(&values[*j]));
+            simplex = order.iter().map(|i| simplex[*i].clone()).collect();
+            values = order.iter().map(|i| values[*i]).collect();
+            if (values[n] - values[0]).abs() <= tolerance * (values[0].abs() + tolerance) {
+                break;
+            }
+
+            let centroid = (0..n)
+                .map(|k| simplex[..n].iter().map(|v| v[k]).sum::<f64>() / n as f64)
+                .collect::<Vec<f64>>();
+            let towards = |t: f64| {
+                centroid
+                    .iter()
+                    .zip(&simplex[n])
+                    .map(|(c, w)| c + t * (w - c))
+                    .collect::<Vec<f64>>()
+            };
+            let reflected = towards(-1.0);
+            let reflected_value = f(&reflected);
+            if reflected_value < values[0] {
+                let expanded = towards(-2.0);
+                let expanded_value = f(&expanded);
+                if expanded_value < reflected_value {
+                    simplex[n] = expanded;
+                    values[n] = expanded_value;
+                } else {
+                    simplex[n] = reflected;
+                    values[n] = reflected_value;
+                }
+            } else if reflected_value < values[n - 1] {
+                simplex[n] = reflected;
+                values[n] = reflected_value;
+            } else {
+                let contracted = towards(0.5);
+                let contracted_value = f(&contracted);
+                if contracted_value < values[n] {
+                    simplex[n] = contracted;
+                    values[n] = contracted_value;
+                } else {
+                    // shrink everything towards the best vertex
+                    for i in 1..=n {
+                        simplex[i] = simplex[0]
+                            .iter()
+                            .zip(&simplex[i])
+                            .map(|(b, v)| b + 0.5 * (v - b))
+                            .collect();
+                        values[i] = f(&simplex[i]);
+                    }
+                }
+            }
+        }
+        let best = (0..=n)
+            .min_by(|i, j| values[*i].total_cmp(&values[*j]))
+            .unwrap();
+        simplex[best].clone()
+    }
+
+    /// Minimises a unimodal `f` on [`low`, `high`] by golden-section search.
+    pub fn golden_section<F: Fn(f64) -> f64>(f: F, low: f64, high: f64, tolerance: f64) -> f64 {
+        let ratio = (5_f64.sqrt() - 1.0) / 2.0;
+        let (mut a, mut b) = (low, high);
+        let mut c = b - ratio * (b - a);
+        let mut d = a + ratio * (b - a);
+        let (mut fc, mut fd) = (f(c), f(d));
+        while (b - a).abs() > tolerance {
+            if fc < fd {
+                b = d;
+                d = c;
+                fd = fc;
+                c = b - ratio * (b - a);
+                fc = f(c);
+            } else {
+                a = c;
+                c = d;
+                fc = fd;
+                d = a + ratio * (b - a);
+                fd = f(d);
+            }
+        }
+        0.5 * (a + b)
+    }
+
+    /// Hessian of `f` at `x` by central finite differences with step `h`.
+    pub fn hessian<F: Fn(&[f64]) -> f64>(f: F, x: &[f64], h: f64) -> Vec<Vec<f64>> {
+        let n = x.len();
+        let shifted = |i: usize, di: f64, j: usize, dj: f64| {
+            let mut y = x.to_vec();
+            y[i] += di;
+            y[j] += dj;
+            f(&y)
+        };
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| {
+                        (shifted(i, h, j, h) - shifted(i, h, j, -h) - shifted(i, -h, j, h)
+                            + shifted(i, -h, j, -h))
+                            / (4.0 * h * h)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Negative log-likelihood of the rank counts under the normalised
+    /// Zipf-Mandelbrot law truncated at the largest observed rank.
+    fn zipf_mandelbrot_nll(bd: &BookData, a: f64, b: f64) -> f64 {
+        if a <= 0.0 || b <= -1.0 {
+            return f64::INFINITY;
+        }
+        let total: i64 = bd.counts.iter().sum();
+        let log_norm = bd
+            .ranks
+            .iter()
+            .map(|r| (*r as f64 + b).powf(-a))
+            .sum::<f64>()
+            .ln();
+        let weighted_log_ranks = bd
+            .ranks
+            .iter()
+            .zip(&bd.counts)
+            .map(|(r, c)| *c as f64 * (*r as f64 + b).ln())
+            .sum::<f64>();
+        a * weighted_log_ranks + total as f64 * log_norm
+    }
+
+    /// Maximum-likelihood fit of the normalised Zipf-Mandelbrot law to the raw
+    /// word counts, with standard errors from the inverse observed Fisher
+    /// information.
+    pub fn fit_zipf_mandelbrot(bd: &BookData, language: &str) -> ZipfMandelbrotResult {
+        let nll = |p: &[f64]| zipf_mandelbrot_nll(bd, p[0], p[1]);
+        let best = nelder_mead(nll, &[1.0, 2.7], &[0.1, 1.0], 1e-12, 2000);
+        let (a, b) = (best[0], best[1]);
+
+        let hess = hessian(nll, &best, 1e-4);
+        let det = hess[0][0] * hess[1][1] - hess[0][1] * hess[1][0];
+        let a_var = hess[1][1] / det;
+        let b_var = hess[0][0] / det;
+
+        ZipfMandelbrotResult {
+            language: language.to_string(),
+            a,
+            b,
+            a_std_err: a_var.sqrt(),
+            b_std_err: b_var.sqrt(),
+            log_likelihood: -nll(&best),
+            ks_distance: zipf_mandelbrot_ks(bd, a, b),
+            vocabulary_size: bd.ranks.len(),
+            total_words: bd.counts.iter().sum(),
+        }
+    }
+
+    /// Largest difference between the empirical and fitted cumulative rank
+    /// distributions.
+    fn zipf_mandelbrot_ks(bd: &BookData, a: f64, b: f64) -> f64 {
+        let total: i64 = bd.counts.iter().sum();
+        let weights = bd
+            .ranks
+            .iter()
+            .map(|r| (*r as f64 + b).powf(-a))
+            .collect::<Vec<f64>>();
+        let norm: f64 = weights.iter().sum();
+        let (mut empirical, mut model, mut distance) = (0.0, 0.0, 0.0_f64);
+        for (count, weight) in bd.counts.iter().zip(weights) {
+            empirical += *count as f64 / total as f64;
+            model += weight / norm;
+            distance = distance.max((empirical - model).abs());
+        }
+        distance
+    }
+}
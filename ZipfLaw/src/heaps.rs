@@ -0,0 +1,173 @@
+pub mod heaps {
+    use csv::Writer;
+    use std::collections::HashMap;
+    use std::error::Error;
+
+    use rand::seq::SliceRandom;
+    use rand::Rng;
+
+    /// Heaps' law V(N) = K N^beta fitted to the vocabulary growth of a book.
+    pub struct HeapsResult {
+        pub language: String,
+        pub k: f64,
+        pub beta: f64,
+        /// Token counts N at which the vocabulary was recorded.
+        pub checkpoints: Vec<usize>,
+        /// V(N) in the original word order.
+        pub vocabulary: Vec<usize>,
+        /// V(N) averaged over random shufflings of the text.
+        pub shuffled_vocabulary: Vec<f64>,
+    }
+
+    impl HeapsResult {
+        pub fn save_results(&self, file_path: &str) -> Result<(), Box<dyn Error>> {
+            let mut wtr = Writer::from_path(file_path)?;
+            wtr.write_record(["tokens", "vocabulary", "shuffled_vocabulary"])?;
+            for i in 0..self.checkpoints.len() {
+                wtr.write_record(&[
+                    self.checkpoints[i].to_string(),
+                    self.vocabulary[i].to_string(),
+                    self.shuffled_vocabulary[i].to_string(),
+                ])?;
+            }
+            wtr.flush()?;
+            Ok(())
+        }
+    }
+
+    /// Roughly `per_decade` logarithmically spaced token counts up to `n_tokens`,
+    /// always ending with `n_tokens` itself.
+    pub fn log_checkpoints(n_tokens: usize, per_decade: usize) -> Vec<usize> {
+        let mut checkpoints = Vec::new();
+        let mut exponent = 0.0;
+        loop {
+            let n = 10_f64.powf(exponent).round() as usize;
+            if n >= n_tokens {
+                break;
+            }
+            if checkpoints.last() != Some(&n) {
+                checkpoints.push(n);
+            }
+            exponent += 1.0 / per_decade as f64;
+        }
+        checkpoints.push(n_tokens);
+        checkpoints
+    }
+
+    fn word_ids(tokens: &[String]) -> (Vec<usize>, usize) {
+        let mut ids = HashMap::new();
+        let sequence = tokens
+            .iter()
+            .map(|t| {
+                let next_id = ids.len();
+                *ids.entry(t.as_str()).or_insert(next_id)
+            })
+            .collect();
+        (sequence, ids.len())
+    }
+
+    fn growth(sequence: &[usize], vocabulary_size: usize, checkpoints: &[usize]) -> Vec<usize> {
+        let mut seen = vec![false; vocabulary_size];
+        let mut distinct = 0;
+        let mut vocabulary = Vec::with_capacity(checkpoints.len());
+        let mut next = 0;
+        for (n, id) in sequence.iter().enumerate() {
+            if !seen[*id] {
+                seen[*id] = true;
+                distinct += 1;
+            }
+            while next < checkpoints.len() && checkpoints[next] == n + 1 {
+                vocabulary.push(distinct);
+                next += 1;
+            }
+        }
+        vocabulary
+    }
+
+    /// Number of distinct words V(N) after each of the first N tokens in
+    /// `checkpoints`, streaming through the text once.
+    pub fn vocabulary_growth(tokens: &[String], checkpoints: &[usize]) -> Vec<usize> {
+        let (sequence, vocabulary_size) = word_ids(tokens);
+        growth(&sequence, vocabulary_size, checkpoints)
+    }
+
+    /// V(N) averaged over `shuffles` random permutations of the tokens, which
+    /// removes the effect of topic changes along the text.
+    pub fn shuffled_vocabulary_growth<R: Rng>(
+        tokens: &[String],
+        checkpoints: &[usize],
+        shuffles: usize,
+        rng: &mut R,
+    ) -> Vec<f64> {
+        let (mut sequence, vocabulary_size) = word_ids(tokens);
+        let mut mean = vec![0.0; checkpoints.len()];
+        for _ in 0..shuffles {
+            sequence.shuffle(rng);
+            for (m, v) in mean
+                .iter_mut()
+                .zip(growth(&sequence, vocabulary_size, checkpoints))
+            {
+                *m += v as f64 / shuffles as f64;
+            }
+        }
+        mean
+    }
+
+    /// Least-squares fit of log V = log K + beta log N, returning (K, beta).
+    pub fn fit_heaps(checkpoints: &[usize], vocabulary: &[f64]) -> (f64, f64) {
+        let points = checkpoints
+            .iter()
+            .zip(vocabulary)
+            .map(|(n, v)| ((*n as f64).ln(), v.ln()))
+            .collect::<Vec<(f64, f64)>>();
+        let count = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+        let covariance = points
+            .iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum::<f64>();
+        let variance = points
+            .iter()
+            .map(|(x, _)| (x - mean_x).powi(2))
+            .sum::<f64>();
+        let beta = covariance / variance;
+        ((mean_y - beta * mean_x).exp(), beta)
+    }
+
+    /// Heaps' exponent implied by a Zipf exponent a > 1, beta = 1/a.
+    pub fn beta_from_zipf(a: f64) -> f64 {
+        if a > 1.0 {
+            1.0 / a
+        } else {
+            1.0
+        }
+    }
+
+    /// Vocabulary growth of `tokens` and its Heaps' law fit. A text without
+    /// tokens has no growth curve to fit, so it is an error.
+    pub fn analyse<R: Rng>(
+        tokens: &[String],
+        language: &str,
+        shuffles: usize,
+        rng: &mut R,
+    ) -> Result<HeapsResult, Box<dyn Error>> {
+        if tokens.is_empty() {
+            return Err(
+                format!("{} has no tokens to measure vocabulary growth on", language).into(),
+            );
+        }
+        let checkpoints = log_checkpoints(tokens.len(), 20);
+        let vocabulary = vocabulary_growth(tokens, &checkpoints);
+        let shuffled_vocabulary = shuffled_vocabulary_growth(tokens, &checkpoints, shuffles, rng);
+        let (k, beta) = fit_heaps(&checkpoints, &shuffled_vocabulary);
+        Ok(HeapsResult {
+            language: language.to_string(),
+            k,
+            beta,
+            checkpoints,
+            vocabulary,
+            shuffled_vocabulary,
+        })
+    }
+}
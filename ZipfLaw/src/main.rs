@@ -1,65 +1,164 @@
+#![allow(clippy::module_inception)]
 use std::collections::HashMap;
+use std::error::Error;
 use std::fs::{self};
+use std::path::{Path, PathBuf};
+use clap::Parser;
+use glob::glob;
 use plotters::prelude::*;
 mod book_data;
-use book_data::book_data::BookData;
+use book_data::book_data::{BookData, TableHeader, SIDECAR_SUFFIX};
 mod zipf_mandelbrot_result;
-use zipf_mandelbrot_result::zipf_mandelbrot_result::ZipfMandelbrotResult;
+mod tokenizer;
+mod fitting;
+use fitting::fitting::fit_zipf_mandelbrot;
+mod heaps;
+use heaps::heaps::HeapsResult;
+use tokenizer::tokenizer::TokenizerOptions;
+mod reader;
+use reader::reader::{count_ngrams, read_tokens};
+mod ngrams;
+mod power_law;
+mod cli;
+mod comparison;
+mod sources;
+use sources::sources::{open_source, save_metadata, BookMetadata};
+mod cache;
+use cache::cache::CountCache;
+use cli::cli::CommonArgs;
+use cli::cli::{Cli, Command, CompareArgs, CountArgs, FitArgs, Model, PlotArgs, Scale, TableFormat};
+use ngrams::ngrams::NgramUnit;
 
-fn read_bookfile(file_path: &str) -> String {
-    let contents = std::fs::read_to_string(file_path)
-        .expect("Something went wrong reading the file");
-    let mut one_line_content = String::new();
-    for line in contents.lines() {
-        one_line_content.push_str(line);
-        one_line_content.push_str(" ");
+fn process_words(tokens: &[String]) -> BookData {
+    let mut word_count = HashMap::new();
+    for word in tokens {
+        let counter = word_count.entry(word.clone()).or_insert(0);
+        *counter += 1;
     }
-    one_line_content
+    BookData::from_counts(word_count)
+}
+
+fn count_book(path: &Path, options: &TokenizerOptions, unit: NgramUnit) -> Result<(BookData, BookMetadata), Box<dyn Error>> {
+    let (reader, metadata) = open_source(path)?;
+    let word_count = count_ngrams(reader, options, unit)?;
+    Ok((BookData::from_counts(word_count), metadata))
+}
+
+/// Whether the input is a CSV or JSON count table saved by `count` rather than a book.
+fn is_count_table(path: &Path) -> bool {
+    matches!(path.extension().and_then(|e| e.to_str()), Some("csv") | Some("json"))
 }
 
-fn remove_punctuation(text: &str) -> String {
-    let mut cleaned_text = String::new();
-    for c in text.chars() {
-        if c.is_alphabetic() || c.is_whitespace() {
-            cleaned_text.push(c);
+/// Counts of a book, read back from a saved CSV or JSON table, taken from the
+/// cache in the output directory or counted and cached. A saved table must
+/// have been counted in `unit`.
+fn load_book(path: &Path, common: &CommonArgs, unit: NgramUnit) -> Result<(BookData, BookMetadata), Box<dyn Error>> {
+    if is_count_table(path) {
+        let file_path = path.to_str().unwrap();
+        let (bd, header) = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => BookData::load_json(file_path)?,
+            _ => BookData::load_results(file_path)?,
+        };
+        if header.unit != unit.label() {
+            return Err(format!(
+                "{} holds {} counts, not {}", path.display(), header.unit, unit.label()
+            ).into());
         }
+        return Ok((bd, header.metadata));
     }
-    cleaned_text.to_ascii_lowercase()
+    let options = common.tokenizer.options();
+    if common.no_cache {
+        return count_book(path, &options, unit);
+    }
+    let cache = CountCache::new(common.output.join("cache"));
+    let key = CountCache::key(path, &options, unit)?;
+    if let Some(cached) = cache.get(&key) {
+        return Ok(cached);
+    }
+    let (bd, metadata) = count_book(path, &options, unit)?;
+    if let Err(error) = cache.put(&key, &bd, &metadata) {
+        eprintln!("Could not cache counts of {}: {}", path.display(), error);
+    }
+    Ok((bd, metadata))
 }
 
-fn process_words(text: &str) -> BookData {
-    let total_words = text.split_whitespace().count();
-    let mut word_count = HashMap::new();
-    for word in text.split_whitespace() {
-        let counter = word_count.entry(word.to_string()).or_insert(0);
-        *counter += 1;
+/// File name without directory and without text, markup or compression extensions,
+/// and without the unit that `count` appends to the names of saved tables.
+fn book_name(path: &Path) -> String {
+    let mut name = path.file_name().unwrap().to_str().unwrap();
+    for extension in [".gz", ".bz2", ".txt", ".html", ".htm", ".xhtml", ".epub", ".csv", ".json"] {
+        name = name.strip_suffix(extension).unwrap_or(name);
     }
-    let mut word_count_vec: Vec<(String, i64)> = word_count.into_iter().collect();
-    word_count_vec.sort_by(|a, b| b.1.cmp(&a.1));
-    let words = word_count_vec.iter().map(|(x, _)| x.clone()).collect::<Vec<String>>();
-    let count = word_count_vec.iter().map(|(_, y)| *y).collect::<Vec<i64>>();
-    let mut frequency = word_count_vec.iter().map(|(_, y)| *y as f64 / total_words as f64).collect::<Vec<f64>>();
-    let rank = (1..=words.len() as i64).collect::<Vec<i64>>();
-
-    // normalze frequency
-    let freq_sum: f64 = frequency.iter().sum();
-    frequency = frequency.iter().map(|x| x / freq_sum).collect::<Vec<f64>>();
-    
-
-    let bd = BookData {
-        ranks: rank,
-        words: words,
-        counts: count,
-        frequencies: frequency,
-    };
-    bd
+    if is_count_table(path) {
+        if let Some((book, unit)) = name.rsplit_once('_') {
+            let n = unit.strip_prefix("word").or_else(|| unit.strip_prefix("char"));
+            if n.is_some_and(|n| n.parse::<usize>().is_ok()) {
+                name = book;
+            }
+        }
+    }
+    name.to_string()
+}
+
+/// Expands files, directories and glob patterns into a sorted list of files,
+/// leaving out the sidecar files of saved tables.
+fn expand_inputs(inputs: &[String]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        if path.is_dir() {
+            let entries = match fs::read_dir(path) {
+                Ok(entries) => entries,
+                Err(error) => {
+                    eprintln!("Skipping {}: {}", input, error);
+                    continue;
+                }
+            };
+            for entry in entries {
+                match entry {
+                    Ok(entry) if entry.path().is_file() => files.push(entry.path()),
+                    Ok(_) => {}
+                    Err(error) => eprintln!("Skipping an entry of {}: {}", input, error),
+                }
+            }
+        } else {
+            let matches = match glob(input) {
+                Ok(matches) => matches,
+                Err(error) => {
+                    eprintln!("Skipping {}: invalid glob pattern: {}", input, error);
+                    continue;
+                }
+            };
+            for entry in matches {
+                match entry {
+                    Ok(entry) if entry.is_file() => files.push(entry),
+                    Ok(_) => {}
+                    Err(error) => eprintln!("Skipping {}", error),
+                }
+            }
+        }
+    }
+    // the headers of saved CSV tables are read along with the tables
+    files.retain(|file| !file.to_string_lossy().ends_with(SIDECAR_SUFFIX));
+    files.sort();
+    files.dedup();
+    if files.is_empty() {
+        eprintln!("No input files match {:?}", inputs);
+    }
+    files
+}
+
+/// Creates `output/name` if needed and returns its path.
+fn output_dir(output: &Path, name: &str) -> PathBuf {
+    let dir = output.join(name);
+    fs::create_dir_all(&dir).expect("Could not create output directory");
+    dir
 }
 
 fn find_teoretical_zipflaw_c(rank_count: i64) -> f64 {
     let mut r_sum = 0.0;
     (1..rank_count).for_each(|r| {r_sum += 1_f64 / r as f64});
-    let c = 1_f64 / r_sum;
-    c   
+    1_f64 / r_sum
 }
 
 fn plot_results(bd: &BookData, file_path: &str, in_log: bool) {
@@ -81,7 +180,7 @@ fn plot_results(bd: &BookData, file_path: &str, in_log: bool) {
             .y_desc("Frequency log")
             .draw().unwrap();
         chart.draw_series(LineSeries::new(
-            rank.clone().into_iter().zip(frequency.into_iter()).map(|(x, y)| (x, y)),
+            rank.clone().into_iter().zip(frequency),
             &BLUE,
         )).unwrap().label("freq/rank");
         chart.draw_series(LineSeries::new(
@@ -89,7 +188,7 @@ fn plot_results(bd: &BookData, file_path: &str, in_log: bool) {
             &RED,
         )).unwrap().label("Theoretical Zipf Law");
         chart.configure_series_labels()
-            .border_style(&BLACK)
+            .border_style(BLACK)
             .label_font(("Arial", 20).into_font())
             .draw().unwrap();
     } else {
@@ -104,7 +203,7 @@ fn plot_results(bd: &BookData, file_path: &str, in_log: bool) {
             .y_desc("Frequency")
             .draw().unwrap();
         chart.draw_series(LineSeries::new(
-            rank.clone().into_iter().zip(frequency.into_iter()).map(|(x, y)| (x, y)),
+            rank.clone().into_iter().zip(frequency),
             &BLUE,
         )).unwrap().label("freq/rank");
         chart.draw_series(LineSeries::new(
@@ -112,87 +211,232 @@ fn plot_results(bd: &BookData, file_path: &str, in_log: bool) {
             &RED,
         )).unwrap().label("Theoretical Zipf Law");
         chart.configure_series_labels()
-            .border_style(&BLACK)
+            .border_style(BLACK)
             .label_font(("Arial", 20).into_font())
             .draw().unwrap();
     };
 }
 
 
-fn task_1and2(folder_path: &str) {
-    let paths = fs::read_dir(folder_path).expect("Could not read directory");
-    for path in paths {
-        let path = path.expect("Could not read path").path();
-        if path.is_file() {
-            let file_path = path.to_str().unwrap();
-            let clear_content = remove_punctuation(&read_bookfile(file_path));
-            let bd = process_words(&clear_content);
-            let file_name = path.file_stem().unwrap().to_str().unwrap();
-            let words_sum: i64 = bd.counts.clone().iter().sum();
-            let csv_file_path = format!("results/csv/{}_{}.csv", file_name, words_sum);
-            bd.save_results(&csv_file_path).unwrap();
-            let lin_plots_file_path = format!("results/plots/{}_lin.png", file_name);
-            plot_results(&bd, &lin_plots_file_path, false);
-            let log_plots_file_path = format!("results/plots/{}_log.png", file_name);
-            plot_results(&bd, &log_plots_file_path, true);
+fn plot_heaps(result: &HeapsResult, file_path: &str) {
+    let root = BitMapBackend::new(file_path, (800, 600)).into_drawing_area();
+    root.fill(&WHITE).unwrap();
+    let max_n = *result.checkpoints.last().unwrap() as f64;
+    let max_v = *result.vocabulary.last().unwrap() as f64;
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Heaps' Law", ("Arial", 50).into_font())
+        .margin(5)
+        .x_label_area_size(40)
+        .y_label_area_size(40)
+        .build_cartesian_2d((1_f64..max_n).log_scale(), (1_f64..max_v * 1.5).log_scale()).unwrap();
+    chart.configure_mesh()
+        .x_desc("Tokens N log")
+        .y_desc("Vocabulary V log")
+        .draw().unwrap();
+    chart.draw_series(LineSeries::new(
+        result.checkpoints.iter().zip(&result.vocabulary).map(|(n, v)| (*n as f64, *v as f64)),
+        &BLUE,
+    )).unwrap().label("V(N) in text order")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+    chart.draw_series(LineSeries::new(
+        result.checkpoints.iter().zip(&result.shuffled_vocabulary).map(|(n, v)| (*n as f64, *v)),
+        &GREEN,
+    )).unwrap().label("V(N) shuffled")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], GREEN));
+    chart.draw_series(LineSeries::new(
+        result.checkpoints.iter().map(|n| (*n as f64, result.k * (*n as f64).powf(result.beta))),
+        &RED,
+    )).unwrap().label(format!("K N^beta, beta = {:.3}", result.beta))
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+    chart.configure_series_labels()
+        .border_style(BLACK)
+        .label_font(("Arial", 20).into_font())
+        .draw().unwrap();
+}
 
+fn count_command(args: &CountArgs) {
+    let unit = args.unit.unit();
+    let mut books = Vec::new();
+    for path in expand_inputs(&args.common.inputs) {
+        let (bd, metadata) = match load_book(&path, &args.common, unit) {
+            Ok(book) => book,
+            Err(error) => {
+                eprintln!("Skipping {}: {}", path.display(), error);
+                continue;
+            }
+        };
+        let name = book_name(&path);
+        let words_sum: i64 = bd.counts.iter().sum();
+        let header = TableHeader { unit: unit.label(), metadata: metadata.clone() };
+        match args.format {
+            TableFormat::Csv => {
+                let csv_file_path = output_dir(&args.common.output, "csv").join(format!("{}_{}.csv", name, unit.label()));
+                bd.save_results(csv_file_path.to_str().unwrap(), &header).unwrap();
+            }
+            TableFormat::Json => {
+                let json_file_path = output_dir(&args.common.output, "json").join(format!("{}_{}.json", name, unit.label()));
+                bd.save_json(json_file_path.to_str().unwrap(), &header).unwrap();
+            }
         }
+        println!("{}: {} tokens, {} types", name, words_sum, bd.ranks.len());
+        books.push((name, metadata));
+    }
+    if !books.is_empty() {
+        // kept apart from the count tables so that their directory can be read back as input
+        let metadata_file_path = output_dir(&args.common.output, "reports").join("metadata.csv");
+        save_metadata(&books, metadata_file_path.to_str().unwrap()).unwrap();
     }
 }
 
-fn calc_zipf_mandelbrot(rank: i64, a: f64, b: f64) -> f64 {
-    1_f64 / (rank as f64 + b).powf(a)
+fn plot_command(args: &PlotArgs) {
+    let unit = args.unit.unit();
+    let plots_dir = output_dir(&args.common.output, "plots");
+    for path in expand_inputs(&args.common.inputs) {
+        let (bd, _) = match load_book(&path, &args.common, unit) {
+            Ok(book) => book,
+            Err(error) => {
+                eprintln!("Skipping {}: {}", path.display(), error);
+                continue;
+            }
+        };
+        let name = book_name(&path);
+        if bd.ranks.is_empty() {
+            eprintln!("Skipping {}: no words to plot", path.display());
+            continue;
+        }
+        if args.scale != Scale::Log {
+            let lin_plots_file_path = plots_dir.join(format!("{}_{}_lin.png", name, unit.label()));
+            plot_results(&bd, lin_plots_file_path.to_str().unwrap(), false);
+        }
+        if args.scale != Scale::Lin {
+            let log_plots_file_path = plots_dir.join(format!("{}_{}_log.png", name, unit.label()));
+            plot_results(&bd, log_plots_file_path.to_str().unwrap(), true);
+        }
+    }
 }
 
-fn fit_zipf_mandelbrot(bd: &BookData) -> (f64, f64) {
-    let a_range = (10..100).map(|x| x as f64 * 0.1).collect::<Vec<f64>>();
-    let b_range = (10..100).map(|x| x as f64 * 0.1).collect::<Vec<f64>>();
-    let mut best_a = 0.0;
-    let mut best_b = 0.0;
-    let mut best_error = f64::MAX;
-    for a in &a_range {
-        for b in &b_range {
-            let mut error = 0.0;
-            for (i, rank) in bd.ranks.iter().enumerate() {
-                let freq = calc_zipf_mandelbrot(*rank, *a, *b);
-                error += (freq - bd.frequencies[i]).powf(2.0);
+fn fit_command(args: &FitArgs) {
+    let options = args.common.tokenizer.options();
+    let unit = args.unit.unit();
+    let mut rng = rand::thread_rng();
+    for path in expand_inputs(&args.common.inputs) {
+        let name = book_name(&path);
+        let (bd, metadata) = match load_book(&path, &args.common, unit) {
+            Ok(book) => book,
+            Err(error) => {
+                eprintln!("Skipping {}: {}", path.display(), error);
+                continue;
+            }
+        };
+        println!(
+            "{}: {} by {} ({})",
+            name, metadata.title.as_deref().unwrap_or("untitled"),
+            metadata.author.as_deref().unwrap_or("unknown author"),
+            metadata.language.as_deref().unwrap_or("unknown language")
+        );
+        if args.model.contains(&Model::ZipfMandelbrot) {
+            let result = fit_zipf_mandelbrot(&bd, &name);
+            println!(
+                "{} {}: a = {:.4} ± {:.4}, b = {:.4} ± {:.4}, log L = {:.1}, N = {}, V = {}",
+                result.language, unit.label(), result.a, result.a_std_err, result.b, result.b_std_err,
+                result.log_likelihood, result.total_words, result.vocabulary_size
+            );
+        }
+        if args.model.contains(&Model::PowerLaw) {
+            // the observations are the counts of the distinct words
+            if let Some(test) = power_law::power_law::test_power_law(&bd.counts, args.bootstrap, &mut rng) {
+                println!(
+                    "{}: alpha = {:.4} ± {:.4}, x_min = {}, n_tail = {}, D = {:.4}, p = {:.2}, log L = {:.1}",
+                    name, test.fit.alpha, test.fit.alpha_std_err, test.fit.x_min,
+                    test.fit.n_tail, test.fit.ks_distance, test.p_value, test.fit.log_likelihood
+                );
+                for comparison in test.comparisons {
+                    println!(
+                        "    vs {}: R = {:.2}, R/(sigma sqrt(n)) = {:.3}, p = {:.3}",
+                        comparison.alternative, comparison.ratio, comparison.normalized_ratio,
+                        comparison.p_value
+                    );
+                }
+            } else {
+                println!("{}: too few distinct counts to fit a power law", name);
             }
-            if error < best_error {
-                best_error = error;
-                best_a = *a;
-                best_b = *b;
+        }
+        if args.model.contains(&Model::Heaps) {
+            if is_count_table(&path) {
+                eprintln!("{}: Heaps' law needs the text in order, not a saved count table", name);
+                continue;
             }
+            let tokens = match open_source(&path).and_then(|(reader, _)| read_tokens(reader, &options)) {
+                Ok(tokens) => tokens,
+                Err(error) => {
+                    eprintln!("Skipping {}: {}", path.display(), error);
+                    continue;
+                }
+            };
+            let heaps = match heaps::heaps::analyse(&tokens, &name, args.shuffles, &mut rng) {
+                Ok(heaps) => heaps,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    continue;
+                }
+            };
+            let zipf = fit_zipf_mandelbrot(&process_words(&tokens), &name);
+            println!(
+                "{}: beta = {:.4}, K = {:.2}, Zipf a = {:.4} predicts beta = {:.4}",
+                heaps.language, heaps.beta, heaps.k, zipf.a, heaps::heaps::beta_from_zipf(zipf.a)
+            );
+            let csv_file_path = output_dir(&args.common.output, "reports").join(format!("{}_heaps.csv", name));
+            heaps.save_results(csv_file_path.to_str().unwrap()).unwrap();
+            let plots_file_path = output_dir(&args.common.output, "plots").join(format!("{}_heaps.png", name));
+            plot_heaps(&heaps, plots_file_path.to_str().unwrap());
         }
     }
-    (best_a, best_b)
 }
 
-fn task_3(folder_path: &str) {
-    let mut results: Vec<ZipfMandelbrotResult> = Vec::new();
-    let paths = fs::read_dir(folder_path).expect("Could not read directory");
-    for path in paths {
-        let path = path.expect("Could not read path").path();
-        if path.is_file() {
-            let file_path = path.to_str().unwrap();
-            let clear_content = remove_punctuation(&read_bookfile(file_path));
-            let bd = process_words(&clear_content);
-            let file_name = path.file_stem().unwrap().to_str().unwrap();
-            let (a,b) = fit_zipf_mandelbrot(&bd);
-            results.push(ZipfMandelbrotResult {
-                language: file_name.to_string(),
-                a: a,
-                b: b,
-                });
+fn compare_command(args: &CompareArgs) {
+    let mut rng = rand::thread_rng();
+    let mut books = Vec::new();
+    let mut summaries = Vec::new();
+    for path in expand_inputs(&args.common.inputs) {
+        let name = book_name(&path);
+        let (bd, metadata) = match load_book(&path, &args.common, NgramUnit::Word(1)) {
+            Ok(book) => book,
+            Err(error) => {
+                eprintln!("Skipping {}: {}", path.display(), error);
+                continue;
+            }
+        };
+        match comparison::comparison::summarise(&bd, &name, metadata, args.bootstrap, &mut rng) {
+            Ok(summary) => summaries.push(summary),
+            Err(error) => {
+                eprintln!("Skipping {}: {}", path.display(), error);
+                continue;
+            }
         }
+        books.push((name, bd));
     }
-    for result in results {
-        println!("{}: a = {}, b = {}", result.language, result.a, result.b);
+    if books.is_empty() {
+        return;
     }
+    let table = comparison::comparison::markdown_table(&summaries);
+    println!("{}", table);
+    let reports_dir = output_dir(&args.common.output, "reports");
+    let csv_file_path = reports_dir.join("comparison.csv");
+    comparison::comparison::save_csv(&summaries, csv_file_path.to_str().unwrap()).unwrap();
+    fs::write(reports_dir.join("comparison.md"), table).expect("Could not write report");
+    let plots_dir = output_dir(&args.common.output, "plots");
+    let parameters_file_path = plots_dir.join("comparison_parameters.png");
+    comparison::comparison::plot_parameters(&summaries, parameters_file_path.to_str().unwrap());
+    let curves_file_path = plots_dir.join("comparison_rank_frequency.png");
+    comparison::comparison::plot_rank_frequency(&books, curves_file_path.to_str().unwrap(), &mut rng);
 }
 
 fn main() {
-    let folder_path = "books";
-    task_1and2(folder_path);
-    let folder_path_2 = "iliad";
-    task_3(folder_path_2);
-}
\ No newline at end of file
+    let cli = Cli::parse();
+    match &cli.command {
+        Command::Count(args) => count_command(args),
+        Command::Plot(args) => plot_command(args),
+        Command::Fit(args) => fit_command(args),
+        Command::Compare(args) => compare_command(args),
+    }
+}
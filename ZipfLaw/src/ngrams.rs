@@ -0,0 +1,79 @@
+pub mod ngrams {
+    use std::collections::{HashMap, VecDeque};
+
+    use unicode_segmentation::UnicodeSegmentation;
+
+    /// Unit whose rank-frequency distribution is counted.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum NgramUnit {
+        /// Sequences of n consecutive words; `Word(1)` counts single words.
+        Word(usize),
+        /// Sequences of n consecutive letters inside a word; `Character(1)`
+        /// counts single letters.
+        Character(usize),
+    }
+
+    impl NgramUnit {
+        /// Short name used in result file names, e.g. "word2" or "char1".
+        pub fn label(&self) -> String {
+            match self {
+                NgramUnit::Word(n) => format!("word{}", n),
+                NgramUnit::Character(n) => format!("char{}", n),
+            }
+        }
+    }
+
+    /// Counts n-grams from a stream of tokens, keeping only the last n - 1
+    /// words between calls so the text never has to be held in memory.
+    pub struct NgramCounter {
+        unit: NgramUnit,
+        window: VecDeque<String>,
+        counts: HashMap<String, i64>,
+    }
+
+    impl NgramCounter {
+        /// Panics if the n-gram length is 0.
+        pub fn new(unit: NgramUnit) -> NgramCounter {
+            let (NgramUnit::Word(n) | NgramUnit::Character(n)) = unit;
+            assert!(n > 0, "n-grams must have a length of at least 1");
+            NgramCounter {
+                unit,
+                window: VecDeque::new(),
+                counts: HashMap::new(),
+            }
+        }
+
+        pub fn push(&mut self, token: &str) {
+            match self.unit {
+                NgramUnit::Word(n) => {
+                    self.window.push_back(token.to_string());
+                    if self.window.len() > n {
+                        self.window.pop_front();
+                    }
+                    if self.window.len() == n {
+                        let key = self
+                            .window
+                            .iter()
+                            .map(|w| w.as_str())
+                            .collect::<Vec<&str>>()
+                            .join(" ");
+                        *self.counts.entry(key).or_insert(0) += 1;
+                    }
+                }
+                NgramUnit::Character(n) => {
+                    let letters = token
+                        .graphemes(true)
+                        .filter(|g| g.chars().any(|c| c.is_alphabetic()))
+                        .collect::<Vec<&str>>();
+                    for gram in letters.windows(n) {
+                        *self.counts.entry(gram.concat()).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        pub fn into_counts(self) -> HashMap<String, i64> {
+            self.counts
+        }
+    }
+}
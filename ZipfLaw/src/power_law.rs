@@ -0,0 +1,300 @@
+pub mod power_law {
+    use rand::Rng;
+    use statrs::function::erf::erfc;
+
+    use crate::fitting::fitting::{golden_section, nelder_mead};
+
+    /// Smallest number of observations in the tail for which an x_min is tried.
+    const MIN_TAIL: usize = 10;
+
+    /// Discrete power law p(x) = x^(-alpha) / zeta(alpha, x_min) for x >= x_min.
+    pub struct PowerLawFit {
+        pub alpha: f64,
+        pub alpha_std_err: f64,
+        pub x_min: i64,
+        /// Number of observations with x >= x_min.
+        pub n_tail: usize,
+        /// Kolmogorov-Smirnov distance between the tail and the fitted law.
+        pub ks_distance: f64,
+        pub log_likelihood: f64,
+    }
+
+    /// Vuong's likelihood-ratio test of the power law against an alternative;
+    /// a positive `ratio` favours the power law, and `p_value` says whether the
+    /// sign of `ratio` is significant.
+    pub struct LikelihoodRatio {
+        pub alternative: &'static str,
+        pub ratio: f64,
+        pub normalized_ratio: f64,
+        pub p_value: f64,
+    }
+
+    pub struct PowerLawTest {
+        pub fit: PowerLawFit,
+        /// Bootstrap p-value of the goodness of fit; the power law is ruled
+        /// out when it is small (below 0.1 in Clauset, Shalizi and Newman).
+        pub p_value: f64,
+        pub comparisons: Vec<LikelihoodRatio>,
+    }
+
+    /// Hurwitz zeta function zeta(s, q) = sum over k >= 0 of (k + q)^(-s) for
+    /// s > 1, by the Euler-Maclaurin formula.
+    pub fn hurwitz_zeta(s: f64, q: f64) -> f64 {
+        const TERMS: usize = 10;
+        // B_2j / (2j)! for j = 1..6
+        const BERNOULLI: [f64; 6] = [
+            1.0 / 12.0,
+            -1.0 / 720.0,
+            1.0 / 30240.0,
+            -1.0 / 1209600.0,
+            1.0 / 47900160.0,
+            -691.0 / 1307674368000.0,
+        ];
+        let head: f64 = (0..TERMS).map(|k| (k as f64 + q).powf(-s)).sum();
+        let x = q + TERMS as f64;
+        let mut tail = x.powf(1.0 - s) / (s - 1.0) + 0.5 * x.powf(-s);
+        // rising factorial s (s + 1) ... (s + 2j - 2) times x^(-s - 2j + 1)
+        let mut factor = s * x.powf(-s - 1.0);
+        for (j, b) in BERNOULLI.iter().enumerate() {
+            tail += b * factor;
+            let k = 2.0 * j as f64;
+            factor *= (s + k + 1.0) * (s + k + 2.0) / (x * x);
+        }
+        head + tail
+    }
+
+    fn fit_alpha(tail: &[i64]) -> f64 {
+        let x_min = tail[0] as f64;
+        let n = tail.len() as f64;
+        let log_sum: f64 = tail.iter().map(|x| (*x as f64).ln()).sum();
+        let nll = |alpha: f64| n * hurwitz_zeta(alpha, x_min).ln() + alpha * log_sum;
+        golden_section(nll, 1.0001, 8.0, 1e-8)
+    }
+
+    /// KS distance between the empirical and the fitted complementary CDFs,
+    /// evaluated at the distinct values of the sorted tail.
+    fn ks_distance(tail: &[i64], alpha: f64) -> f64 {
+        let n = tail.len() as f64;
+        let norm = hurwitz_zeta(alpha, tail[0] as f64);
+        let mut distance: f64 = 0.0;
+        let mut i = 0;
+        while i < tail.len() {
+            let empirical = (tail.len() - i) as f64 / n;
+            let model = hurwitz_zeta(alpha, tail[i] as f64) / norm;
+            distance = distance.max((empirical - model).abs());
+            let value = tail[i];
+            while i < tail.len() && tail[i] == value {
+                i += 1;
+            }
+        }
+        distance
+    }
+
+    /// Discrete power-law MLE with x_min chosen to minimise the KS distance,
+    /// following Clauset, Shalizi and Newman (2009); `None` when fewer than
+    /// `MIN_TAIL` positive observations are available.
+    pub fn fit_power_law(data: &[i64]) -> Option<PowerLawFit> {
+        let mut sorted = data
+            .iter()
+            .copied()
+            .filter(|x| *x > 0)
+            .collect::<Vec<i64>>();
+        sorted.sort_unstable();
+        let mut best: Option<(f64, usize, f64)> = None;
+        let mut start = 0;
+        while sorted.len() - start >= MIN_TAIL {
+            let tail = &sorted[start..];
+            let alpha = fit_alpha(tail);
+            let distance = ks_distance(tail, alpha);
+            if best.is_none_or(|(d, _, _)| distance < d) {
+                best = Some((distance, start, alpha));
+            }
+            let value = sorted[start];
+            while start < sorted.len() && sorted[start] == value {
+                start += 1;
+            }
+        }
+        let (ks_distance, start, alpha) = best?;
+        let tail = &sorted[start..];
+        let x_min = tail[0];
+        let n = tail.len() as f64;
+        let log_likelihood = tail
+            .iter()
+            .map(|x| power_law_log_pmf(*x, alpha, x_min))
+            .sum::<f64>();
+        // observed Fisher information n d^2/dalpha^2 ln zeta(alpha, x_min)
+        let h = 1e-4;
+        let ln_zeta = |a: f64| hurwitz_zeta(a, x_min as f64).ln();
+        let curvature = (ln_zeta(alpha + h) - 2.0 * ln_zeta(alpha) + ln_zeta(alpha - h)) / (h * h);
+        Some(PowerLawFit {
+            alpha,
+            alpha_std_err: 1.0 / (n * curvature).sqrt(),
+            x_min,
+            n_tail: tail.len(),
+            ks_distance,
+            log_likelihood,
+        })
+    }
+
+    fn power_law_log_pmf(x: i64, alpha: f64, x_min: i64) -> f64 {
+        -alpha * (x as f64).ln() - hurwitz_zeta(alpha, x_min as f64).ln()
+    }
+
+    /// Draws from the fitted discrete power law with the approximation of
+    /// Clauset, Shalizi and Newman, eq. (D.6).
+    fn sample_power_law<R: Rng>(alpha: f64, x_min: i64, rng: &mut R) -> i64 {
+        let u: f64 = rng.gen();
+        ((x_min as f64 - 0.5) * (1.0 - u).powf(-1.0 / (alpha - 1.0)) + 0.5).floor() as i64
+    }
+
+    /// Semi-parametric bootstrap p-value: the fraction of synthetic data sets,
+    /// drawn from the fitted law above x_min and resampled from the data below
+    /// it, whose own best fit is worse than the fit to the real data.
+    pub fn bootstrap_p_value<R: Rng>(
+        data: &[i64],
+        fit: &PowerLawFit,
+        samples: usize,
+        rng: &mut R,
+    ) -> f64 {
+        let body = data
+            .iter()
+            .copied()
+            .filter(|x| *x > 0 && *x < fit.x_min)
+            .collect::<Vec<i64>>();
+        let n = body.len() + fit.n_tail;
+        let tail_probability = fit.n_tail as f64 / n as f64;
+        let mut worse = 0;
+        for _ in 0..samples {
+            let synthetic = (0..n)
+                .map(|_| {
+                    if body.is_empty() || rng.gen::<f64>() < tail_probability {
+                        sample_power_law(fit.alpha, fit.x_min, rng)
+                    } else {
+                        body[rng.gen_range(0..body.len())]
+                    }
+                })
+                .collect::<Vec<i64>>();
+            if fit_power_law(&synthetic).is_none_or(|f| f.ks_distance >= fit.ks_distance) {
+                worse += 1;
+            }
+        }
+        worse as f64 / samples as f64
+    }
+
+    /// Probability of integer x for a continuous distribution discretised by
+    /// rounding, P(x) = S(x - 1/2) - S(x + 1/2), truncated at x_min.
+    fn discretised_log_pmf<S: Fn(f64) -> f64>(x: i64, x_min: i64, survival: S) -> f64 {
+        let x = x as f64;
+        ((survival(x - 0.5) - survival(x + 0.5)) / survival(x_min as f64 - 0.5)).ln()
+    }
+
+    fn log_normal_survival(x: f64, mu: f64, sigma: f64) -> f64 {
+        0.5 * erfc((x.ln() - mu) / (sigma * std::f64::consts::SQRT_2))
+    }
+
+    fn stretched_exponential_survival(x: f64, lambda: f64, beta: f64) -> f64 {
+        (-(x / lambda).powf(beta)).exp()
+    }
+
+    /// Pointwise log-likelihoods of the tail under the maximum-likelihood
+    /// fit of a two-parameter alternative.
+    fn fit_alternative<L: Fn(i64, &[f64]) -> f64>(
+        tail: &[i64],
+        log_pmf: L,
+        start: &[f64],
+    ) -> Vec<f64> {
+        let nll = |p: &[f64]| {
+            let value = -tail.iter().map(|x| log_pmf(*x, p)).sum::<f64>();
+            if value.is_finite() {
+                value
+            } else {
+                f64::INFINITY
+            }
+        };
+        let best = nelder_mead(nll, start, &[0.5, 0.5], 1e-10, 2000);
+        tail.iter().map(|x| log_pmf(*x, &best)).collect()
+    }
+
+    /// Vuong's test from the pointwise log-likelihood ratios.
+    fn vuong(alternative: &'static str, differences: &[f64]) -> LikelihoodRatio {
+        let n = differences.len() as f64;
+        let ratio: f64 = differences.iter().sum();
+        let mean = ratio / n;
+        let variance = differences.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / n;
+        let sigma = variance.sqrt();
+        LikelihoodRatio {
+            alternative,
+            ratio,
+            normalized_ratio: ratio / (sigma * n.sqrt()),
+            p_value: erfc(ratio.abs() / (sigma * (2.0 * n).sqrt())),
+        }
+    }
+
+    /// Compares the power law with log-normal and stretched-exponential tails
+    /// above the same x_min.
+    pub fn compare_alternatives(data: &[i64], fit: &PowerLawFit) -> Vec<LikelihoodRatio> {
+        let tail = data
+            .iter()
+            .copied()
+            .filter(|x| *x >= fit.x_min)
+            .collect::<Vec<i64>>();
+        let power_law = tail
+            .iter()
+            .map(|x| power_law_log_pmf(*x, fit.alpha, fit.x_min))
+            .collect::<Vec<f64>>();
+        let mean_log = tail.iter().map(|x| (*x as f64).ln()).sum::<f64>() / tail.len() as f64;
+        let x_min = fit.x_min;
+
+        let log_normal = fit_alternative(
+            &tail,
+            |x, p| {
+                if p[1] <= 0.0 {
+                    return f64::NEG_INFINITY;
+                }
+                discretised_log_pmf(x, x_min, |y| log_normal_survival(y, p[0], p[1]))
+            },
+            &[mean_log, 1.0],
+        );
+        let stretched_exponential = fit_alternative(
+            &tail,
+            |x, p| {
+                if p[0] <= 0.0 || p[1] <= 0.0 {
+                    return f64::NEG_INFINITY;
+                }
+                discretised_log_pmf(x, x_min, |y| stretched_exponential_survival(y, p[0], p[1]))
+            },
+            &[mean_log.exp(), 0.5],
+        );
+
+        [
+            ("log-normal", log_normal),
+            ("stretched exponential", stretched_exponential),
+        ]
+        .into_iter()
+        .map(|(name, alternative)| {
+            let differences = power_law
+                .iter()
+                .zip(&alternative)
+                .map(|(p, a)| p - a)
+                .collect::<Vec<f64>>();
+            vuong(name, &differences)
+        })
+        .collect()
+    }
+
+    /// Full analysis: fit, bootstrap goodness of fit and comparisons.
+    pub fn test_power_law<R: Rng>(
+        data: &[i64],
+        bootstrap_samples: usize,
+        rng: &mut R,
+    ) -> Option<PowerLawTest> {
+        let fit = fit_power_law(data)?;
+        let p_value = bootstrap_p_value(data, &fit, bootstrap_samples, rng);
+        let comparisons = compare_alternatives(data, &fit);
+        Some(PowerLawTest {
+            fit,
+            p_value,
+            comparisons,
+        })
+    }
+}
@@ -0,0 +1,108 @@
+pub mod reader {
+    use std::collections::HashMap;
+    use std::fs::File;
+    use std::io::{self, BufRead, BufReader, Read};
+    use std::path::Path;
+
+    use bzip2::read::MultiBzDecoder;
+    use flate2::read::MultiGzDecoder;
+
+    use crate::ngrams::ngrams::{NgramCounter, NgramUnit};
+    use crate::tokenizer::tokenizer::{tokenize, TokenizerOptions};
+
+    /// Bytes read from the input at a time; text is tokenised chunk by chunk
+    /// so that only the vocabulary, not the whole book, is kept in memory.
+    const CHUNK_SIZE: usize = 1 << 16;
+
+    /// Most bytes kept waiting for an ASCII whitespace byte, so that text
+    /// without any, such as Chinese or words separated by no-break spaces,
+    /// still goes through in bounded pieces.
+    const MAX_PENDING: usize = 4 * CHUNK_SIZE;
+
+    /// Opens a plain text, `.gz` or `.bz2` book as a buffered reader.
+    pub fn open_book(file_path: &Path) -> io::Result<Box<dyn BufRead>> {
+        let file = File::open(file_path)?;
+        let extension = file_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        Ok(match extension.as_deref() {
+            Some("gz") => Box::new(BufReader::new(MultiGzDecoder::new(file))),
+            Some("bz2") => Box::new(BufReader::new(MultiBzDecoder::new(file))),
+            _ => Box::new(BufReader::new(file)),
+        })
+    }
+
+    /// Where to cut the pending bytes: after the last ASCII whitespace byte,
+    /// or, once more than `MAX_PENDING` bytes are waiting, after the last
+    /// Unicode whitespace character or else before the last character.
+    fn cut_position(pending: &[u8]) -> Option<usize> {
+        if let Some(cut) = pending.iter().rposition(|b| b.is_ascii_whitespace()) {
+            return Some(cut + 1);
+        }
+        if pending.len() <= MAX_PENDING {
+            return None;
+        }
+        // start of the last character, which may not be complete yet
+        let boundary = pending
+            .iter()
+            .rposition(|b| b & 0xC0 != 0x80)
+            .filter(|&b| b > 0)
+            .unwrap_or(pending.len());
+        let text = match std::str::from_utf8(&pending[..boundary]) {
+            Ok(text) => text,
+            Err(error) => std::str::from_utf8(&pending[..error.valid_up_to()]).unwrap(),
+        };
+        let cut = text
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(boundary, |(i, c)| i + c.len_utf8());
+        Some(cut)
+    }
+
+    /// Calls `f` with consecutive pieces of the text, cut as in
+    /// `cut_position` so that neither words nor UTF-8 sequences are split,
+    /// except in runs of over `MAX_PENDING` bytes without whitespace.
+    fn for_each_chunk<R: Read, F: FnMut(&str)>(mut reader: R, mut f: F) -> io::Result<()> {
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        let mut pending: Vec<u8> = Vec::new();
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            pending.extend_from_slice(&buffer[..read]);
+            if let Some(cut) = cut_position(&pending) {
+                let rest = pending.split_off(cut);
+                f(&String::from_utf8_lossy(&pending));
+                pending = rest;
+            }
+        }
+        f(&String::from_utf8_lossy(&pending));
+        Ok(())
+    }
+
+    /// Streams through the reader and counts word or character n-grams, with
+    /// peak memory proportional to the vocabulary rather than to the text.
+    pub fn count_ngrams<R: Read>(
+        reader: R,
+        options: &TokenizerOptions,
+        unit: NgramUnit,
+    ) -> io::Result<HashMap<String, i64>> {
+        let mut counter = NgramCounter::new(unit);
+        for_each_chunk(reader, |text| {
+            for word in tokenize(text, options) {
+                counter.push(&word);
+            }
+        })?;
+        Ok(counter.into_counts())
+    }
+
+    /// All tokens of the book in text order, for analyses that depend on it.
+    pub fn read_tokens<R: Read>(reader: R, options: &TokenizerOptions) -> io::Result<Vec<String>> {
+        let mut tokens = Vec::new();
+        for_each_chunk(reader, |text| tokens.extend(tokenize(text, options)))?;
+        Ok(tokens)
+    }
+}
@@ -0,0 +1,454 @@
+pub mod sources {
+    use std::error::Error;
+    use std::fs::File;
+    use std::io::{self, BufRead, Cursor, Read};
+    use std::path::Path;
+
+    use csv::Writer;
+    use serde::{Deserialize, Serialize};
+    use zip::ZipArchive;
+
+    use crate::reader::reader::open_book;
+
+    /// Lines searched for the Gutenberg start marker before the file is
+    /// treated as an ordinary text.
+    const HEADER_LINES: usize = 1000;
+
+    /// Longest piece of a line read at once while looking for the Gutenberg
+    /// markers, so that text without line breaks is not held in memory whole.
+    const MAX_LINE: usize = 1 << 16;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    pub enum SourceFormat {
+        Text,
+        Html,
+        Epub,
+    }
+
+    /// Bibliographic information found in the book file itself.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct BookMetadata {
+        pub format: SourceFormat,
+        /// Whether Project Gutenberg header and licence were stripped.
+        pub gutenberg: bool,
+        pub title: Option<String>,
+        pub author: Option<String>,
+        pub language: Option<String>,
+    }
+
+    impl BookMetadata {
+        pub fn new(format: SourceFormat) -> BookMetadata {
+            BookMetadata {
+                format,
+                gutenberg: false,
+                title: None,
+                author: None,
+                language: None,
+            }
+        }
+
+        /// Fills fields that are still unknown from `other`.
+        fn merge(&mut self, other: BookMetadata) {
+            self.gutenberg |= other.gutenberg;
+            self.title = self.title.take().or(other.title);
+            self.author = self.author.take().or(other.author);
+            self.language = self.language.take().or(other.language);
+        }
+    }
+
+    /// Format from the extension, looking through `.gz` and `.bz2`.
+    fn source_format(file_path: &Path) -> SourceFormat {
+        let name = file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let name = name
+            .strip_suffix(".gz")
+            .or(name.strip_suffix(".bz2"))
+            .unwrap_or(&name);
+        if name.ends_with(".epub") {
+            SourceFormat::Epub
+        } else if name.ends_with(".html") || name.ends_with(".htm") || name.ends_with(".xhtml") {
+            SourceFormat::Html
+        } else {
+            SourceFormat::Text
+        }
+    }
+
+    /// Opens a plain text, HTML or EPUB book (the first two optionally
+    /// compressed) and returns its text without Project Gutenberg boilerplate
+    /// together with the metadata found along the way.
+    pub fn open_source(file_path: &Path) -> io::Result<(Box<dyn Read>, BookMetadata)> {
+        let format = source_format(file_path);
+        let (text, mut metadata): (Box<dyn BufRead>, BookMetadata) = match format {
+            SourceFormat::Text => (open_book(file_path)?, BookMetadata::new(format)),
+            SourceFormat::Html => {
+                let mut html = String::new();
+                open_book(file_path)?.read_to_string(&mut html)?;
+                let metadata = html_metadata(&html);
+                (Box::new(Cursor::new(html_to_text(&html))), metadata)
+            }
+            SourceFormat::Epub => {
+                let (text, metadata) = read_epub(file_path)?;
+                (Box::new(Cursor::new(text)), metadata)
+            }
+        };
+        let (body, header) = strip_gutenberg(text)?;
+        metadata.merge(header);
+        Ok((body, metadata))
+    }
+
+    fn is_marker(line: &str, kind: &str) -> bool {
+        let line = line.trim().to_ascii_uppercase();
+        line.starts_with("***")
+            && line.contains("PROJECT GUTENBERG")
+            && (line.contains(&format!("{} OF THE", kind))
+                || line.contains(&format!("{} OF THIS", kind)))
+    }
+
+    /// Value of a "Title: ..." style field of the Gutenberg header.
+    fn header_field(line: &str, field: &str) -> Option<String> {
+        let value = line.trim().strip_prefix(field)?.strip_prefix(':')?.trim();
+        (!value.is_empty()).then(|| value.to_string())
+    }
+
+    /// Skips the Gutenberg header up to the "*** START OF" marker, reading the
+    /// title, author and language from it, and stops the text at the
+    /// "*** END OF" marker. Texts without a marker are passed through intact.
+    fn strip_gutenberg(mut reader: Box<dyn BufRead>) -> io::Result<(Box<dyn Read>, BookMetadata)> {
+        let mut metadata = BookMetadata::new(SourceFormat::Text);
+        let mut consumed = Vec::new();
+        let mut bytes = Vec::new();
+        for _ in 0..HEADER_LINES {
+            bytes.clear();
+            // decoded lossily like the rest of the text, so that Latin-1 and
+            // other legacy encodings do not fail
+            let mut piece = reader.by_ref().take(MAX_LINE as u64);
+            if piece.read_until(b'\n', &mut bytes)? == 0 {
+                break;
+            }
+            // a line this long is not part of a Gutenberg header
+            if bytes.len() == MAX_LINE && !bytes.ends_with(b"\n") {
+                consumed.extend_from_slice(&bytes);
+                break;
+            }
+            let line = String::from_utf8_lossy(&bytes);
+            if is_marker(&line, "START") {
+                metadata.gutenberg = true;
+                let body = GutenbergBody {
+                    inner: reader,
+                    line: Vec::new(),
+                    position: 0,
+                    finished: false,
+                };
+                return Ok((Box::new(body), metadata));
+            }
+            metadata.title = metadata.title.or(header_field(&line, "Title"));
+            metadata.author = metadata.author.or(header_field(&line, "Author"));
+            metadata.language = metadata.language.or(header_field(&line, "Language"));
+            consumed.extend_from_slice(&bytes);
+        }
+        Ok((
+            Box::new(Cursor::new(consumed).chain(reader)),
+            BookMetadata::new(SourceFormat::Text),
+        ))
+    }
+
+    /// Text of a Gutenberg book after the start marker, up to the end marker.
+    struct GutenbergBody<R> {
+        inner: R,
+        line: Vec<u8>,
+        position: usize,
+        finished: bool,
+    }
+
+    impl<R: BufRead> Read for GutenbergBody<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            while self.position == self.line.len() {
+                if self.finished {
+                    return Ok(0);
+                }
+                self.line.clear();
+                self.position = 0;
+                let mut piece = self.inner.by_ref().take(MAX_LINE as u64);
+                if piece.read_until(b'\n', &mut self.line)? == 0
+                    || is_marker(&String::from_utf8_lossy(&self.line), "END")
+                {
+                    self.line.clear();
+                    self.finished = true;
+                }
+            }
+            let n = buf.len().min(self.line.len() - self.position);
+            buf[..n].copy_from_slice(&self.line[self.position..self.position + n]);
+            self.position += n;
+            Ok(n)
+        }
+    }
+
+    /// Contents of every `<...>` tag of an HTML or XML document.
+    fn tags(document: &str) -> impl Iterator<Item = &str> {
+        document
+            .split('<')
+            .skip(1)
+            .filter_map(|part| part.split_once('>').map(|(tag, _)| tag))
+    }
+
+    fn tag_name(tag: &str) -> String {
+        tag.trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase()
+    }
+
+    /// Value of `name="..."` (or single-quoted) inside a tag.
+    fn attribute(tag: &str, name: &str) -> Option<String> {
+        let lower = tag.to_ascii_lowercase();
+        let mut from = 0;
+        while let Some(found) = lower[from..].find(name) {
+            let start = from + found;
+            from = start + name.len();
+            let preceded = start == 0
+                || matches!(
+                    lower.as_bytes()[start - 1],
+                    b' ' | b'\t' | b'\n' | b'\r' | b':'
+                );
+            let rest = tag[from..].trim_start();
+            let (true, Some(rest)) = (preceded, rest.strip_prefix('=')) else {
+                continue;
+            };
+            let rest = rest.trim_start();
+            let quote = rest.chars().next()?;
+            if quote == '"' || quote == '\'' {
+                return rest[1..].split(quote).next().map(decode_entities);
+            }
+            return rest.split_whitespace().next().map(decode_entities);
+        }
+        None
+    }
+
+    /// Text of the first `<name ...>text</name>` element, ignoring case.
+    fn element_text(document: &str, name: &str) -> Option<String> {
+        let lower = document.to_ascii_lowercase();
+        let mut from = 0;
+        while let Some(found) = lower[from..].find(&format!("<{}", name)) {
+            let start = from + found;
+            from = start + 1;
+            let open_end = start + lower[start..].find('>')? + 1;
+            if tag_name(&document[start + 1..open_end - 1]) != name {
+                continue;
+            }
+            let close = open_end + lower[open_end..].find(&format!("</{}", name))?;
+            let text = html_to_text(&document[open_end..close]);
+            let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+            return (!text.is_empty()).then_some(text);
+        }
+        None
+    }
+
+    fn decode_entities(text: &str) -> String {
+        let mut decoded = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('&') {
+            decoded.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+            let character = entity.and_then(|e| match e {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" | "rsquo" => Some('\''),
+                "nbsp" => Some(' '),
+                "mdash" => Some('\u{2014}'),
+                "ndash" => Some('\u{2013}'),
+                _ => e
+                    .strip_prefix("#x")
+                    .or(e.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or(e.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            });
+            match (entity, character) {
+                (Some(entity), Some(character)) => {
+                    decoded.push(character);
+                    rest = &rest[entity.len() + 2..];
+                }
+                _ => {
+                    decoded.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        decoded.push_str(rest);
+        decoded
+    }
+
+    /// Visible text of an HTML or XHTML document: tags are dropped, block
+    /// elements become line breaks and `<head>`, `<script>` and `<style>`
+    /// contents are skipped.
+    pub fn html_to_text(html: &str) -> String {
+        let mut text = String::with_capacity(html.len() / 2);
+        let mut hidden: Option<String> = None;
+        let mut rest = html;
+        while let Some(start) = rest.find('<') {
+            if hidden.is_none() {
+                text.push_str(&decode_entities(&rest[..start]));
+            }
+            rest = &rest[start + 1..];
+            if let Some(comment) = rest.strip_prefix("!--") {
+                rest = comment.split_once("-->").map_or("", |(_, after)| after);
+                continue;
+            }
+            let Some((tag, after)) = rest.split_once('>') else {
+                rest = "";
+                break;
+            };
+            rest = after;
+            let name = tag_name(tag);
+            match &hidden {
+                Some(open) if tag.starts_with('/') && name == *open => hidden = None,
+                Some(_) => {}
+                None if matches!(name.as_str(), "head" | "script" | "style")
+                    && !tag.starts_with('/')
+                    && !tag.ends_with('/') =>
+                {
+                    hidden = Some(name);
+                }
+                None if matches!(
+                    name.as_str(),
+                    "p" | "br"
+                        | "div"
+                        | "li"
+                        | "tr"
+                        | "td"
+                        | "h1"
+                        | "h2"
+                        | "h3"
+                        | "h4"
+                        | "h5"
+                        | "h6"
+                        | "blockquote"
+                        | "section"
+                        | "title"
+                ) =>
+                {
+                    text.push('\n')
+                }
+                None => {}
+            }
+        }
+        if hidden.is_none() {
+            text.push_str(&decode_entities(rest));
+        }
+        text
+    }
+
+    fn html_metadata(html: &str) -> BookMetadata {
+        let mut metadata = BookMetadata::new(SourceFormat::Html);
+        metadata.title = element_text(html, "title");
+        for tag in tags(html) {
+            match tag_name(tag).as_str() {
+                "html" => metadata.language = metadata.language.take().or(attribute(tag, "lang")),
+                "meta" => {
+                    let name = attribute(tag, "name")
+                        .unwrap_or_default()
+                        .to_ascii_lowercase();
+                    let content = attribute(tag, "content");
+                    match name.as_str() {
+                        "author" | "dc.creator" => {
+                            metadata.author = metadata.author.take().or(content)
+                        }
+                        "dc.title" => metadata.title = content.or(metadata.title.take()),
+                        "dc.language" => metadata.language = content.or(metadata.language.take()),
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+        metadata
+    }
+
+    fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> io::Result<String> {
+        let mut entry = archive.by_name(name).map_err(io::Error::other)?;
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        Ok(content)
+    }
+
+    /// Resolves `href` relative to the directory of the package document.
+    fn resolve(base: &str, href: &str) -> String {
+        let href = href.split('#').next().unwrap_or("").replace("%20", " ");
+        let mut parts = base.split('/').collect::<Vec<&str>>();
+        parts.pop();
+        for part in href.split('/') {
+            match part {
+                ".." => {
+                    parts.pop();
+                }
+                "." | "" => {}
+                _ => parts.push(part),
+            }
+        }
+        parts.join("/")
+    }
+
+    /// Text of the spine documents of an EPUB in reading order, and the Dublin
+    /// Core title, creator and language of its package document.
+    fn read_epub(file_path: &Path) -> io::Result<(String, BookMetadata)> {
+        let mut archive = ZipArchive::new(File::open(file_path)?).map_err(io::Error::other)?;
+        let container = read_entry(&mut archive, "META-INF/container.xml")?;
+        let package_path = tags(&container)
+            .find(|tag| tag_name(tag) == "rootfile")
+            .and_then(|tag| attribute(tag, "full-path"))
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "EPUB has no package document")
+            })?;
+        let package = read_entry(&mut archive, &package_path)?;
+
+        let mut metadata = BookMetadata::new(SourceFormat::Epub);
+        metadata.title = element_text(&package, "dc:title");
+        metadata.author = element_text(&package, "dc:creator");
+        metadata.language = element_text(&package, "dc:language");
+
+        let manifest = tags(&package)
+            .filter(|tag| tag_name(tag) == "item")
+            .filter_map(|tag| Some((attribute(tag, "id")?, attribute(tag, "href")?)))
+            .collect::<Vec<(String, String)>>();
+        let mut text = String::new();
+        for tag in tags(&package).filter(|tag| tag_name(tag) == "itemref") {
+            let Some(id) = attribute(tag, "idref") else {
+                continue;
+            };
+            let Some((_, href)) = manifest.iter().find(|(item, _)| *item == id) else {
+                continue;
+            };
+            let document = read_entry(&mut archive, &resolve(&package_path, href))?;
+            text.push_str(&html_to_text(&document));
+            text.push('\n');
+        }
+        Ok((text, metadata))
+    }
+
+    pub fn save_metadata(
+        books: &[(String, BookMetadata)],
+        file_path: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut wtr = Writer::from_path(file_path)?;
+        wtr.write_record(["book", "format", "gutenberg", "title", "author", "language"])?;
+        for (name, metadata) in books {
+            wtr.write_record(&[
+                name.clone(),
+                format!("{:?}", metadata.format).to_lowercase(),
+                metadata.gutenberg.to_string(),
+                metadata.title.clone().unwrap_or_default(),
+                metadata.author.clone().unwrap_or_default(),
+                metadata.language.clone().unwrap_or_default(),
+            ])?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
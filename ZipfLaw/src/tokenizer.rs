@@ -0,0 +1,138 @@
+pub mod tokenizer {
+    use caseless::default_case_fold_str;
+    use clap::ValueEnum;
+    use unicode_normalization::UnicodeNormalization;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    /// What to do with apostrophes inside words such as "don't" or "l'homme".
+    #[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+    pub enum ApostropheMode {
+        /// "don't" stays a single token.
+        Keep,
+        /// "don't" becomes "don" and "t".
+        Split,
+        /// "don't" becomes "dont".
+        Remove,
+    }
+
+    /// What to do with hyphenated compounds such as "well-known".
+    #[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+    pub enum HyphenMode {
+        /// "well-known" stays a single token.
+        Keep,
+        /// "well-known" becomes "well" and "known".
+        Split,
+    }
+
+    /// What to do with tokens containing digits such as "1984" or "3rd".
+    #[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+    pub enum DigitMode {
+        Keep,
+        Drop,
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TokenizerOptions {
+        pub apostrophes: ApostropheMode,
+        pub hyphens: HyphenMode,
+        pub digits: DigitMode,
+        /// Unicode case folding, so that "Żółw" and "żółw" or "ΣΟΦΟΣ" and
+        /// "σοφος" count as the same word.
+        pub case_fold: bool,
+        /// NFC normalisation, so that precomposed and combining-mark spellings
+        /// of the same letter are merged.
+        pub normalize: bool,
+    }
+
+    impl Default for TokenizerOptions {
+        fn default() -> Self {
+            TokenizerOptions {
+                apostrophes: ApostropheMode::Keep,
+                hyphens: HyphenMode::Keep,
+                digits: DigitMode::Drop,
+                case_fold: true,
+                normalize: true,
+            }
+        }
+    }
+
+    fn is_apostrophe(c: char) -> bool {
+        matches!(c, '\'' | '\u{2019}' | '\u{02BC}')
+    }
+
+    fn is_hyphen(segment: &str) -> bool {
+        matches!(segment, "-" | "\u{2010}" | "\u{2011}")
+    }
+
+    fn is_word(segment: &str) -> bool {
+        segment.chars().any(|c| c.is_alphanumeric())
+    }
+
+    /// Splits text into words on Unicode (UAX #29) word boundaries and
+    /// normalises them according to `options`.
+    pub fn tokenize(text: &str, options: &TokenizerOptions) -> Vec<String> {
+        let text = if options.normalize {
+            text.nfc().collect::<String>()
+        } else {
+            text.to_string()
+        };
+        let segments = text.split_word_bounds().collect::<Vec<&str>>();
+
+        // UAX #29 already keeps "don't" together but splits "well-known",
+        // so hyphenated compounds are glued back here when requested
+        let mut words: Vec<String> = Vec::new();
+        let mut joinable = false;
+        for (i, segment) in segments.iter().enumerate() {
+            if is_word(segment) {
+                if joinable {
+                    let last = words.last_mut().unwrap();
+                    last.push('-');
+                    last.push_str(segment);
+                } else {
+                    words.push(segment.to_string());
+                }
+                joinable = false;
+            } else {
+                joinable = options.hyphens == HyphenMode::Keep
+                    && is_hyphen(segment)
+                    && i > 0
+                    && is_word(segments[i - 1])
+                    && i + 1 < segments.len()
+                    && is_word(segments[i + 1]);
+            }
+        }
+
+        let mut tokens = Vec::with_capacity(words.len());
+        for word in words {
+            if options.digits == DigitMode::Drop && word.chars().any(|c| c.is_numeric()) {
+                continue;
+            }
+            let parts = match options.apostrophes {
+                ApostropheMode::Keep => vec![word.replace(is_apostrophe, "'")],
+                ApostropheMode::Split => word
+                    .split(is_apostrophe)
+                    .filter(|p| !p.is_empty())
+                    .map(|p| p.to_string())
+                    .collect(),
+                ApostropheMode::Remove => vec![word.replace(is_apostrophe, "")],
+            };
+            for part in parts {
+                tokens.push(normalize_case(&part, options));
+            }
+        }
+        tokens
+    }
+
+    fn normalize_case(word: &str, options: &TokenizerOptions) -> String {
+        if !options.case_fold {
+            return word.to_string();
+        }
+        let folded = default_case_fold_str(word);
+        // folding may decompose characters, so recompose them again
+        if options.normalize {
+            folded.nfc().collect()
+        } else {
+            folded
+        }
+    }
+}
@@ -1,7 +1,18 @@
 pub mod zipf_mandelbrot_result {
+  /// Normalised Zipf-Mandelbrot law p(r) = (r + b)^(-a) / H(a, b) fitted to a
+  /// book, where H(a, b) sums (r + b)^(-a) over all observed ranks.
   pub struct ZipfMandelbrotResult {
     pub language: String,
     pub a: f64,
     pub b: f64,
+    /// Standard errors of `a` and `b` from the observed Fisher information.
+    pub a_std_err: f64,
+    pub b_std_err: f64,
+    pub log_likelihood: f64,
+    /// Kolmogorov-Smirnov distance between the empirical and fitted rank
+    /// distributions, as a measure of fit quality.
+    pub ks_distance: f64,
+    pub vocabulary_size: usize,
+    pub total_words: i64,
   }
 }
\ No newline at end of file